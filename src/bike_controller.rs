@@ -1,285 +1,596 @@
-#[cfg(feature = "real-bluetooth")]
-use kdri::{KettlerConnection, scan_devices};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Notify;
 use tokio::time;
 use anyhow::{Result, bail};
-use crate::training_program::{TrainingProgram, ProgramExecutionState};
+use crate::config::Config;
+use crate::connection::{BackoffPolicy, ConnectionInfo, ConnectionState, LivenessMonitor, LivenessPolicy};
+use crate::training_program::{AutoPause, AutoPauseEvent, TrainingProgram, ProgramExecutionState, IntervalTarget, PowerLimits, SeekAction};
+use crate::training_program::{ProgramMetrics, ProgramWithMetrics, ValidationError, ValidationErrors, ValidationReason};
+use crate::training_program::{INTENSITY_PERCENT_RANGE, POWER_OFFSET_MAX, is_valid_program_id};
+use crate::builtin_programs;
+use crate::formats::{self, Imported, ProgramFormat};
+use crate::storage;
+use crate::trainer::{BrakeMode, DeviceInfo, DeviceLimits, Telemetry, Trainer};
+use crate::trainer::actor::{TelemetrySnapshot, TrainerHandle};
 
 #[derive(Debug, Clone)]
 pub struct BikeData {
     pub speed: f32,
     pub rpm: u16,
-    pub power: u16,
+    /// Consigne de puissance en watts
+    pub power_target: u16,
+    /// Puissance réellement produite, si l'appareil la rapporte
+    pub power_actual: Option<u16>,
+    pub pulse: Option<u16>,
+    pub distance: Option<u16>,
+    pub energy: Option<u16>,
+    pub time: Option<u16>,
+    pub device_state: Option<String>,
+    pub brake_mode: Option<BrakeMode>,
+    pub brake_level: Option<u8>,
     pub connected: bool,
 }
 
 pub struct BikeController {
-    #[cfg(feature = "real-bluetooth")]
-    connection: Arc<Mutex<Option<KettlerConnection>>>,
+    // Backend matériel (Kettler, simulation...), piloté par son thread d'E/S
+    trainer: TrainerHandle,
     data: Arc<Mutex<BikeData>>,
-    #[cfg(feature = "real-bluetooth")]
-    reconnect_attempts: Arc<Mutex<u32>>,
-    // Stockage des programmes d'entraînement
+    // Limites rapportées par l'appareil après connexion
+    device_limits: Arc<Mutex<DeviceLimits>>,
+    // Plage de puissance utilisée tant que l'appareil ne rapporte pas la sienne
+    fallback_power_limits: PowerLimits,
+    // Appareil préféré : les (re)connexions ciblent toujours cette adresse
+    preferred_device: Arc<Mutex<Option<DeviceInfo>>>,
+    preferred_device_path: PathBuf,
+    // État de la connexion et politique de reconnexion
+    connection: Arc<Mutex<ConnectionInfo>>,
+    connection_changed: Arc<Notify>,
+    backoff: BackoffPolicy,
+    // Critères de détection d'une connexion inactive
+    liveness: LivenessPolicy,
+    // Stockage des programmes d'entraînement (un fichier JSON par programme)
     programs: Arc<Mutex<HashMap<String, TrainingProgram>>>,
+    programs_dir: PathBuf,
+    // Puissance de récupération par défaut pendant une pause
+    pause_power: Option<u16>,
+    // Pause automatique par défaut (arrêt du pédalage)
+    auto_pause: AutoPause,
+    // FTP du cycliste, pour les puissances relatives des fichiers importés
+    ftp: u16,
     // État du programme en cours d'exécution
     active_program: Arc<Mutex<Option<ProgramExecutionState>>>,
 }
 
 impl BikeController {
     // Initialise le contrôleur sans nécessairement se connecter immédiatement
-    #[cfg(feature = "real-bluetooth")]
-    pub async fn new() -> Result<Arc<Self>> {
+    pub async fn new(trainer: Box<dyn Trainer>, config: &Config) -> Result<Arc<Self>> {
         let data = BikeData {
             speed: 0.0,
             rpm: 0,
-            power: 0,
+            power_target: 0,
+            power_actual: None,
+            pulse: None,
+            distance: None,
+            energy: None,
+            time: None,
+            device_state: None,
+            brake_mode: None,
+            brake_level: None,
             connected: false,
         };
 
+        let preferred_device_path = config.data_dir.join("preferred_device.json");
+        let preferred_device = storage::read_json::<DeviceInfo>(&preferred_device_path)
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️  Appareil préféré illisible, ignoré : {:?}", e);
+                None
+            });
+        if let Some(ref device) = preferred_device {
+            println!("📌 Appareil préféré : {} ({})", device.name, device.address);
+        }
+
+        let programs_dir = config.data_dir.join("programs");
+        let mut programs = load_programs(&programs_dir);
+        println!("📚 {} programme(s) chargé(s) depuis {}", programs.len(), programs_dir.display());
+
+        // Les programmes intégrés priment sur un éventuel fichier de même ID
+        for program in builtin_programs::builtin_programs() {
+            if programs.contains_key(&program.id) {
+                eprintln!("⚠️  Programme enregistré '{}' masqué par le programme intégré", program.id);
+            }
+            programs.insert(program.id.clone(), program);
+        }
+
         let controller = Arc::new(BikeController {
-            connection: Arc::new(Mutex::new(None)),
+            trainer: TrainerHandle::spawn(trainer)?,
             data: Arc::new(Mutex::new(data)),
-            reconnect_attempts: Arc::new(Mutex::new(0)),
-            programs: Arc::new(Mutex::new(HashMap::new())),
+            device_limits: Arc::new(Mutex::new(DeviceLimits::default())),
+            fallback_power_limits: config.fallback_power_limits,
+            preferred_device: Arc::new(Mutex::new(preferred_device)),
+            preferred_device_path,
+            connection: Arc::new(Mutex::new(ConnectionInfo::new())),
+            connection_changed: Arc::new(Notify::new()),
+            backoff: config.backoff,
+            liveness: config.liveness,
+            programs: Arc::new(Mutex::new(programs)),
+            programs_dir,
+            pause_power: config.pause_power,
+            auto_pause: config.auto_pause,
+            ftp: config.ftp,
             active_program: Arc::new(Mutex::new(None)),
         });
 
-        // Lancer la tentative de connexion en arrière-plan
-        let controller_clone = controller.clone();
-        tokio::spawn(async move {
-            controller_clone.try_initial_connection().await;
-        });
-
         Ok(controller)
     }
 
-    #[cfg(feature = "real-bluetooth")]
-    async fn try_initial_connection(&self) {
-        println!("🔍 Recherche d'appareils Kettler...");
+    /// Modifie l'état de la connexion et réveille la boucle de supervision
+    fn update_connection(&self, update: impl FnOnce(&mut ConnectionInfo)) {
+        update(&mut self.connection.lock().unwrap());
+        self.connection_changed.notify_one();
+    }
 
-        for attempt in 1..=3 {
-            match self.attempt_connection().await {
-                Ok(_) => {
-                    println!("✅ Connecté avec succès !");
-                    return;
-                }
-                Err(e) => {
-                    eprintln!("⚠️  Tentative {}/3 échouée : {:?}", attempt, e);
-                    if attempt < 3 {
-                        println!("🔄 Nouvelle tentative dans 3 secondes...");
-                        tokio::time::sleep(Duration::from_secs(3)).await;
-                    }
-                }
-            }
+    fn set_connection_state(&self, state: ConnectionState) {
+        let previous = {
+            let mut connection = self.connection.lock().unwrap();
+            std::mem::replace(&mut connection.state, state)
+        };
+        if previous != state {
+            println!("🔌 Connexion : {:?} → {:?}", previous, state);
         }
+        self.connection_changed.notify_one();
+    }
 
-        println!("⚠️  Impossible de se connecter pour le moment.");
-        println!("   Le serveur continue de fonctionner. Réessai automatique toutes les 30 secondes...");
+    /// Tente une connexion à `address`, sinon à l'appareil préféré, sinon au dernier trouvé.
+    /// En cas d'échec, la prochaine tentative est programmée selon la politique de backoff.
+    async fn attempt_connection(&self, address: Option<String>) -> Result<DeviceInfo> {
+        // Une seule tentative à la fois
+        let previous_device = {
+            let mut connection = self.connection.lock().unwrap();
+            if connection.state.is_attempting() {
+                bail!("Une tentative de connexion est déjà en cours");
+            }
+            connection.state = ConnectionState::Scanning;
+            connection.clear_retry();
+            connection.device.clone()
+        };
+        self.connection_changed.notify_one();
+
+        match self.scan_and_connect(address).await {
+            Ok(device) => {
+                self.update_connection(|c| {
+                    c.state = ConnectionState::Connected;
+                    c.attempts = 0;
+                    c.last_error = None;
+                    c.device = Some(device.clone());
+                    c.clear_retry();
+                });
+                self.data.lock().unwrap().connected = true;
+                println!("✅ Connecté à {} ({})", device.name, device.address);
+
+                // Retenir le premier appareil utilisé pour que les reconnexions le ciblent
+                if self.preferred_device.lock().unwrap().is_none() {
+                    self.save_preferred_device(&device);
+                }
 
-        // Continuer à essayer en arrière-plan
-        let controller = self;
-        loop {
-            tokio::time::sleep(Duration::from_secs(30)).await;
-            println!("🔄 Tentative de connexion automatique...");
-            if let Ok(_) = controller.attempt_connection().await {
-                println!("✅ Connecté avec succès !");
-                break;
+                Ok(device)
+            }
+            Err(e) if self.trainer.is_connected() => {
+                // Échec avant de toucher à la connexion existante (appareil introuvable...)
+                eprintln!("⚠️  Connexion impossible, appareil actuel conservé : {:#}", e);
+                self.update_connection(|c| {
+                    c.state = ConnectionState::Connected;
+                    c.last_error = Some(format!("{:#}", e));
+                    c.device = previous_device;
+                });
+                Err(e)
+            }
+            Err(e) => {
+                self.connection_failed(&e);
+                Err(e)
             }
         }
     }
 
-    #[cfg(feature = "real-bluetooth")]
-    async fn attempt_connection(&self) -> Result<()> {
-        let connection = Arc::clone(&self.connection);
+    async fn scan_and_connect(&self, address: Option<String>) -> Result<DeviceInfo> {
+        let address = address
+            .or_else(|| self.preferred_device.lock().unwrap().as_ref().map(|d| d.address.clone()));
+
+        let devices = self.scan_devices().await?;
+        let device = match address {
+            Some(address) => devices.into_iter()
+                .find(|d| d.address.eq_ignore_ascii_case(&address))
+                .ok_or_else(|| anyhow::anyhow!("Appareil {} introuvable", address))?,
+            None => devices.into_iter().last()
+                .ok_or_else(|| anyhow::anyhow!("Aucun appareil trouvé"))?,
+        };
 
-        let new_conn = tokio::task::spawn_blocking(move || {
-            let devices = scan_devices().map_err(|e| anyhow::anyhow!("Scan failed: {:?}", e))?;
-            let device = devices.into_iter().last().ok_or_else(|| anyhow::anyhow!("No Kettler device found"))?;
-            println!("📱 Appareil trouvé : {}", device.get_name());
-            println!("🔗 Connexion en cours...");
-            device.connect().map_err(|e| anyhow::anyhow!("Connect failed: {}", e))
-        }).await??;
+        self.set_connection_state(ConnectionState::Connecting);
+        self.data.lock().unwrap().connected = false;
 
-        *connection.lock().unwrap() = Some(new_conn);
+        let (device, limits) = self.trainer.connect(Some(device.address)).await?;
 
-        {
-            let mut data = self.data.lock().unwrap();
-            data.connected = true;
-        }
+        self.store_limits(limits);
+        Ok(device)
+    }
 
-        *self.reconnect_attempts.lock().unwrap() = 0;
+    /// Enregistre un échec de connexion et programme la tentative suivante
+    fn connection_failed(&self, error: &anyhow::Error) {
+        let backoff = self.backoff;
+        self.update_connection(|c| {
+            c.attempts += 1;
+            c.last_error = Some(format!("{:#}", error));
+            c.device = None;
+            if c.auto_reconnect {
+                let delay = backoff.delay(c.attempts);
+                eprintln!("⚠️  Tentative de connexion {} échouée : {:#}", c.attempts, error);
+                eprintln!("   Nouvelle tentative dans {:.0}s", delay.as_secs_f32());
+                c.schedule_retry(delay);
+            } else {
+                c.state = ConnectionState::Idle;
+                c.clear_retry();
+            }
+        });
+        self.data.lock().unwrap().connected = false;
+    }
 
-        Ok(())
+    fn save_preferred_device(&self, device: &DeviceInfo) {
+        if let Err(e) = storage::write_json_atomic(&self.preferred_device_path, device) {
+            eprintln!("⚠️  Impossible d'enregistrer l'appareil préféré : {:?}", e);
+        }
+        *self.preferred_device.lock().unwrap() = Some(device.clone());
+        println!("📌 Appareil préféré : {} ({})", device.name, device.address);
     }
 
-    #[cfg(feature = "real-bluetooth")]
-    async fn reconnect(&self) -> Result<()> {
-        // Vérifier d'abord si on est déjà connecté
-        {
-            let conn = self.connection.lock().unwrap();
-            if conn.is_some() {
-                println!("ℹ️  Déjà connecté, pas besoin de reconnexion");
-                return Ok(());
-            }
+    /// Recherche les appareils à portée. Refusée pendant un programme tant que l'appareil est
+    /// connecté : le scan occupe le thread d'E/S plusieurs secondes et retarderait les consignes.
+    pub async fn scan_devices(&self) -> Result<Vec<DeviceInfo>> {
+        if self.trainer.is_connected() && self.active_program.lock().unwrap().is_some() {
+            bail!("Recherche impossible pendant un programme : arrêtez-le d'abord");
         }
 
-        let current_attempts = {
-            let mut attempts = self.reconnect_attempts.lock().unwrap();
-            *attempts += 1;
-            *attempts
-        };
+        println!("🔍 Recherche d'appareils ({})...", self.trainer.name());
+        let devices = self.trainer.scan().await?;
+        println!("   {} appareil(s) trouvé(s)", devices.len());
 
-        if current_attempts > 5 {
-            println!("❌ Trop de tentatives de reconnexion, réinitialisation du compteur");
-            *self.reconnect_attempts.lock().unwrap() = 0;
-            return Err(anyhow::anyhow!("Max reconnection attempts reached, will retry later"));
+        Ok(devices)
+    }
+
+    /// Appareil préféré enregistré
+    pub async fn get_preferred_device(&self) -> Option<DeviceInfo> {
+        self.preferred_device.lock().unwrap().clone()
+    }
+
+    /// Se connecte à l'appareil choisi et l'enregistre comme appareil préféré
+    pub async fn connect_device(&self, address: &str) -> Result<DeviceInfo> {
+        self.connection.lock().unwrap().auto_reconnect = true;
+        let device = self.attempt_connection(Some(address.to_string())).await?;
+        self.save_preferred_device(&device);
+        Ok(device)
+    }
+
+    /// État observable de la connexion
+    pub async fn get_connection(&self) -> ConnectionInfo {
+        self.connection.lock().unwrap().clone()
+    }
+
+    /// Connexion manuelle : réactive la reconnexion automatique et tente immédiatement
+    pub async fn connect(&self) -> Result<DeviceInfo> {
+        self.update_connection(|c| {
+            c.auto_reconnect = true;
+            c.attempts = 0;
+        });
+        self.attempt_connection(None).await
+    }
+
+    /// Déconnexion manuelle : ferme la connexion et suspend la reconnexion automatique
+    pub async fn disconnect(&self) -> Result<()> {
+        if self.connection.lock().unwrap().state.is_attempting() {
+            bail!("Une tentative de connexion est en cours, réessayez dans quelques secondes");
         }
 
-        println!("🔄 Tentative de reconnexion ({}/5)...", current_attempts);
+        self.trainer.disconnect().await?;
 
-        self.attempt_connection().await?;
+        self.update_connection(|c| {
+            c.state = ConnectionState::Idle;
+            c.auto_reconnect = false;
+            c.device = None;
+            c.clear_retry();
+        });
+        self.data.lock().unwrap().connected = false;
+        println!("⏏️  Déconnecté manuellement");
 
-        println!("✅ Reconnecté avec succès !");
         Ok(())
     }
 
-    #[cfg(feature = "real-bluetooth")]
+    /// Lance la boucle de supervision : connexion, polling des mesures et reconnexion
     pub fn start_polling(self: Arc<Self>, interval_sec: u64) {
+        self.trainer.start_polling(Duration::from_secs(interval_sec));
         tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs(interval_sec));
-            let mut consecutive_errors = 0u32;
-
-            loop {
-                interval.tick().await;
-                match self.update_data().await {
-                    Ok(_) => {
-                        consecutive_errors = 0;
-                    }
-                    Err(e) => {
-                        consecutive_errors += 1;
-                        eprintln!("⚠️  Erreur mise à jour données vélo : {:?}", e);
-
-                        if consecutive_errors >= 3 {
-                            // Vérifier si on est vraiment déconnecté avant de tenter une reconnexion
-                            let is_connected = {
-                                let conn = self.connection.lock().unwrap();
-                                conn.is_some()
-                            };
+            let _ = self.attempt_connection(None).await;
+            self.connection_loop(interval_sec).await;
+        });
+    }
 
-                            if !is_connected {
-                                eprintln!("🔌 Connexion perdue, tentative de reconnexion...");
-
-                                // Marquer comme déconnecté
-                                {
-                                    let mut data = self.data.lock().unwrap();
-                                    data.connected = false;
-                                }
-
-                                // Tentative de reconnexion
-                                if let Err(reconnect_err) = self.reconnect().await {
-                                    eprintln!("❌ Échec de reconnexion : {:?}", reconnect_err);
-                                    time::sleep(Duration::from_secs(5)).await;
-                                } else {
-                                    consecutive_errors = 0;
-                                }
-                            } else {
-                                // Connexion existe mais erreur de communication
-                                // Attendre un peu avant de réessayer
-                                eprintln!("⚠️  Erreur de communication (connexion existe)");
-                                time::sleep(Duration::from_secs(2)).await;
+    async fn connection_loop(&self, interval_sec: u64) {
+        // Au-delà de ce délai sans nouvelle mesure, le thread d'E/S est considéré bloqué
+        let watchdog = Duration::from_secs(interval_sec * 5);
+        let mut snapshots = self.trainer.subscribe();
+        let mut consecutive_errors = 0u32;
+        let mut liveness = LivenessMonitor::new(self.liveness);
+        let mut was_connected = false;
+
+        loop {
+            let (state, deadline) = {
+                let connection = self.connection.lock().unwrap();
+                (connection.state, connection.retry_deadline)
+            };
+
+            let connected = matches!(state, ConnectionState::Connected | ConnectionState::Degraded);
+            if connected && !was_connected {
+                // Nouvelle connexion : repartir d'un historique vierge
+                consecutive_errors = 0;
+                liveness.reset();
+            }
+            was_connected = connected;
+
+            match state {
+                ConnectionState::Connected | ConnectionState::Degraded => {
+                    tokio::select! {
+                        changed = snapshots.changed() => {
+                            if changed.is_err() {
+                                eprintln!("❌ Thread d'E/S de l'appareil arrêté");
+                                return;
+                            }
+                            let snapshot = snapshots.borrow_and_update().clone();
+                            if let Some(snapshot) = snapshot {
+                                self.poll_once(state, snapshot, &mut consecutive_errors, &mut liveness).await;
+                            }
+                        }
+                        _ = time::sleep(watchdog) => {
+                            let snapshot = TelemetrySnapshot {
+                                telemetry: Err(format!("aucune mesure reçue depuis {}s", watchdog.as_secs())),
+                                limits: None,
+                            };
+                            self.poll_once(state, snapshot, &mut consecutive_errors, &mut liveness).await;
+                        }
+                        _ = self.connection_changed.notified() => {}
+                    }
+                }
+                ConnectionState::Backoff => {
+                    let deadline = deadline.unwrap_or_else(std::time::Instant::now);
+                    tokio::select! {
+                        _ = time::sleep_until(deadline.into()) => {
+                            // L'état a pu changer pendant l'attente (connexion manuelle)
+                            if self.connection.lock().unwrap().state == ConnectionState::Backoff {
                                 consecutive_errors = 0;
+                                let _ = self.attempt_connection(None).await;
                             }
                         }
+                        _ = self.connection_changed.notified() => {}
                     }
                 }
+                // Idle : attendre une connexion manuelle.
+                // Scanning/Connecting : une tentative manuelle est en cours.
+                ConnectionState::Idle | ConnectionState::Scanning | ConnectionState::Connecting => {
+                    self.connection_changed.notified().await;
+                }
             }
-        });
+        }
     }
 
-    #[cfg(feature = "real-bluetooth")]
-    async fn update_data(&self) -> Result<()> {
-        let connection = Arc::clone(&self.connection);
+    /// Traite les mesures publiées par le thread d'E/S, avec passage en mode dégradé ou reconnexion si besoin
+    async fn poll_once(&self, state: ConnectionState, snapshot: TelemetrySnapshot,
+                       consecutive_errors: &mut u32, liveness: &mut LivenessMonitor) {
+        if let Some(limits) = snapshot.limits {
+            self.store_limits(limits);
+        }
 
-        // On doit exécuter toutes les opérations kdri dans un seul spawn_blocking
-        let result = tokio::task::spawn_blocking(move || {
-            let mut conn_guard = connection.lock().unwrap();
+        match snapshot.telemetry.map_err(|e| anyhow::anyhow!(e)) {
+            Ok(telemetry) => {
+                // kdri renvoie ses dernières valeurs en cache même si le lien est mort
+                if let Some(reason) = liveness.observe(&telemetry, std::time::Instant::now()) {
+                    self.drop_stale_connection(&reason).await;
+                    return;
+                }
 
-            if let Some(ref mut conn) = *conn_guard {
-                let speed = conn.get_speed();
-                let rpm = conn.get_rpm();
-                let power = conn.get_power_target();
-                Ok((speed, rpm, power))
-            } else {
-                Err(anyhow::anyhow!("No active connection"))
+                self.store_telemetry(telemetry);
+                *consecutive_errors = 0;
+                if state == ConnectionState::Degraded {
+                    self.update_connection(|c| {
+                        c.state = ConnectionState::Connected;
+                        c.last_error = None;
+                    });
+                    println!("✅ Communication rétablie");
+                }
             }
-        }).await?;
+            Err(e) => {
+                *consecutive_errors += 1;
+                eprintln!("⚠️  Erreur mise à jour données vélo : {:#}", e);
 
-        let (speed_opt, rpm_opt, power_opt) = result?;
+                // Une déconnexion manuelle a pu intervenir pendant la lecture
+                if !matches!(self.connection.lock().unwrap().state,
+                             ConnectionState::Connected | ConnectionState::Degraded) {
+                    return;
+                }
+
+                if !self.trainer.is_connected() {
+                    eprintln!("🔌 Connexion perdue");
+                    self.connection_failed(&e);
+                } else if let Some(reason) = liveness.record_failure(std::time::Instant::now()) {
+                    self.drop_stale_connection(&reason).await;
+                } else if *consecutive_errors >= 3 {
+                    // Connexion existe mais erreurs de communication répétées
+                    self.update_connection(|c| {
+                        c.state = ConnectionState::Degraded;
+                        c.last_error = Some(format!("{:#}", e));
+                    });
+                }
+            }
+        }
+    }
 
-        let speed = speed_opt.map(|v| v as f32 / 10.0).unwrap_or(0.0);
-        let rpm = rpm_opt.unwrap_or(0);
-        let power = power_opt.unwrap_or(0);
+    /// Ferme une connexion jugée inactive et laisse la supervision reconnecter l'appareil
+    async fn drop_stale_connection(&self, reason: &str) {
+        // Une déconnexion manuelle a pu intervenir pendant la lecture
+        if !matches!(self.connection.lock().unwrap().state,
+                     ConnectionState::Connected | ConnectionState::Degraded) {
+            return;
+        }
+
+        eprintln!("💤 Connexion inactive ({}), fermeture", reason);
+        if let Err(e) = self.trainer.disconnect().await {
+            eprintln!("⚠️  Erreur lors de la fermeture de la connexion : {:?}", e);
+        }
 
+        self.connection_failed(&anyhow::anyhow!("Connexion inactive : {}", reason));
+    }
+
+    fn store_telemetry(&self, telemetry: Telemetry) {
         let mut data = self.data.lock().unwrap();
-        data.speed = speed;
-        data.rpm = rpm;
-        data.power = power;
+        data.speed = telemetry.speed;
+        data.rpm = telemetry.rpm;
+        data.power_target = telemetry.power_target;
+        data.power_actual = telemetry.power_actual;
+        data.pulse = telemetry.pulse;
+        data.distance = telemetry.distance;
+        data.energy = telemetry.energy;
+        data.time = telemetry.time;
+        data.device_state = telemetry.device_state;
+        data.brake_mode = telemetry.brake_mode;
+        data.brake_level = telemetry.brake_level;
         data.connected = true;
+    }
 
-        Ok(())
+    fn store_limits(&self, limits: DeviceLimits) {
+        let mut current = self.device_limits.lock().unwrap();
+        if *current != limits {
+            println!("📏 Limites de l'appareil : puissance {:?}-{:?}W, niveau de frein {:?}-{:?}",
+                     limits.power_min, limits.power_max,
+                     limits.brake_level_min, limits.brake_level_max);
+            *current = limits;
+        }
     }
 
     pub async fn get_data(&self) -> BikeData {
         self.data.lock().unwrap().clone()
     }
 
-    pub async fn get_power(&self) -> u16 {
-        self.data.lock().unwrap().power
+    /// Limites rapportées par l'appareil
+    pub async fn get_device_limits(&self) -> DeviceLimits {
+        *self.device_limits.lock().unwrap()
     }
 
-    pub async fn set_power(&self, level: u16) -> Result<()> {
-        if level < 25 || level > 400 {
-            bail!("Niveau de puissance hors plage (25-400)");
+    /// Plage de puissance effective : celle de l'appareil si connue, sinon la plage de repli configurée
+    pub fn power_limits(&self) -> PowerLimits {
+        let limits = self.device_limits.lock().unwrap();
+        match (limits.power_min, limits.power_max) {
+            (Some(min), Some(max)) if min <= max => PowerLimits { min, max },
+            _ => self.fallback_power_limits,
         }
+    }
 
-        let connection = Arc::clone(&self.connection);
+    /// Indique si la plage de puissance effective provient de l'appareil
+    pub fn power_limits_reported(&self) -> bool {
+        let limits = self.device_limits.lock().unwrap();
+        matches!((limits.power_min, limits.power_max), (Some(min), Some(max)) if min <= max)
+    }
 
-        tokio::task::spawn_blocking(move || {
-            let mut conn_guard = connection.lock().unwrap();
-            if let Some(ref mut conn) = *conn_guard {
-                conn.set_power(level);
-                Ok(())
-            } else {
-                Err(anyhow::anyhow!("No active connection"))
-            }
-        }).await??;
+    pub async fn set_power(&self, level: u16) -> Result<()> {
+        let limits = self.power_limits();
+        if !limits.contains(level) {
+            bail!("Niveau de puissance hors plage ({}-{})", limits.min, limits.max);
+        }
+
+        // Une consigne en watts repasse l'appareil en puissance constante
+        let switch_mode = self.data.lock().unwrap().brake_mode == Some(BrakeMode::ConstantBrake);
+        if switch_mode {
+            self.trainer.set_brake_mode(BrakeMode::ConstantPower).await?;
+        }
+        self.trainer.set_power(level).await?;
 
         let mut data = self.data.lock().unwrap();
-        data.power = level;
+        data.power_target = level;
+        data.brake_mode = Some(BrakeMode::ConstantPower);
 
         println!("⚡ Puissance définie à {}W", level);
 
         Ok(())
     }
 
+    /// Change le mode de freinage (puissance constante / niveau de frein constant)
+    pub async fn set_brake_mode(&self, mode: BrakeMode) -> Result<()> {
+        self.trainer.set_brake_mode(mode).await?;
+
+        self.data.lock().unwrap().brake_mode = Some(mode);
+
+        println!("⚙️  Mode de freinage : {:?}", mode);
+
+        Ok(())
+    }
+
+    /// Bornes du niveau de frein rapportées par l'appareil
+    pub async fn get_brake_level_range(&self) -> Option<(u8, u8)> {
+        let limits = self.device_limits.lock().unwrap();
+        Some((limits.brake_level_min?, limits.brake_level_max?))
+    }
+
+    /// Définit le niveau de frein (passe l'appareil en niveau constant si nécessaire)
+    pub async fn set_brake_level(&self, level: u8) -> Result<()> {
+        let Some((min, max)) = self.get_brake_level_range().await else {
+            bail!("Limites du niveau de frein inconnues (appareil non connecté ?)");
+        };
+
+        if !(min..=max).contains(&level) {
+            bail!("Niveau de frein hors plage ({}-{})", min, max);
+        }
+
+        let switch_mode = self.data.lock().unwrap().brake_mode != Some(BrakeMode::ConstantBrake);
+        if switch_mode {
+            self.trainer.set_brake_mode(BrakeMode::ConstantBrake).await?;
+        }
+        self.trainer.set_brake_level(level).await?;
+
+        let mut data = self.data.lock().unwrap();
+        data.brake_level = Some(level);
+        data.brake_mode = Some(BrakeMode::ConstantBrake);
+
+        println!("⚙️  Niveau de frein défini à {}", level);
+
+        Ok(())
+    }
+
+    /// Applique une consigne d'intervalle (puissance ou niveau de frein)
+    async fn apply_target(&self, target: IntervalTarget) -> Result<()> {
+        match target {
+            IntervalTarget::Power { power_target } => self.set_power(power_target).await,
+            IntervalTarget::BrakeLevel { brake_level } => self.set_brake_level(brake_level).await,
+            // Une rampe démarre à sa puissance initiale ; le programme fait ensuite évoluer la consigne
+            IntervalTarget::Ramp { power_start, .. } => self.set_power(power_start).await,
+        }
+    }
+
     // ===== Gestion des programmes d'entraînement =====
 
     /// Crée un nouveau programme d'entraînement
     pub async fn create_program(&self, program: TrainingProgram) -> Result<()> {
-        if !program.is_valid() {
-            bail!("Programme invalide : vérifiez que tous les intervalles ont une durée > 0 et une puissance entre 25W et 400W");
-        }
+        let mut errors = program.validate(&self.power_limits(), self.get_brake_level_range().await);
 
         let mut programs = self.programs.lock().unwrap();
 
         if programs.contains_key(&program.id) {
-            bail!("Un programme avec l'ID '{}' existe déjà", program.id);
+            errors.push(ValidationError::new("id", ValidationReason::Duplicate));
+        }
+        if !errors.is_empty() {
+            return Err(ValidationErrors(errors).into());
         }
 
+        storage::write_json_atomic(&self.program_path(&program.id), &program)?;
+
         println!("📝 Nouveau programme créé : {} ({} intervalles, {}s total)",
-                 program.name, program.intervals.len(), program.total_duration());
+                 program.name, program.interval_count(), program.total_duration());
 
         programs.insert(program.id.clone(), program);
         Ok(())
@@ -287,24 +598,30 @@ impl BikeController {
 
     /// Met à jour un programme existant
     pub async fn update_program(&self, program: TrainingProgram) -> Result<()> {
-        if !program.is_valid() {
-            bail!("Programme invalide");
+        let errors = program.validate(&self.power_limits(), self.get_brake_level_range().await);
+        if !errors.is_empty() {
+            return Err(ValidationErrors(errors).into());
         }
 
         let mut programs = self.programs.lock().unwrap();
 
-        if !programs.contains_key(&program.id) {
-            bail!("Programme '{}' introuvable", program.id);
+        match programs.get(&program.id) {
+            None => bail!("Programme '{}' introuvable", program.id),
+            Some(existing) if existing.builtin => {
+                bail!("Le programme intégré '{}' est en lecture seule : clonez-le pour le modifier", program.id)
+            }
+            Some(_) => {}
         }
 
         // Vérifier qu'on ne modifie pas un programme en cours d'exécution
         let active = self.active_program.lock().unwrap();
-        if let Some(ref state) = *active {
-            if state.program_id == program.id {
-                bail!("Impossible de modifier un programme en cours d'exécution");
-            }
+        if let Some(ref state) = *active
+            && state.program_id == program.id {
+            bail!("Impossible de modifier un programme en cours d'exécution");
         }
 
+        storage::write_json_atomic(&self.program_path(&program.id), &program)?;
+
         println!("📝 Programme mis à jour : {}", program.name);
         programs.insert(program.id.clone(), program);
         Ok(())
@@ -314,25 +631,95 @@ impl BikeController {
     pub async fn delete_program(&self, program_id: &str) -> Result<()> {
         // Vérifier qu'on ne supprime pas un programme en cours d'exécution
         let active = self.active_program.lock().unwrap();
-        if let Some(ref state) = *active {
-            if state.program_id == program_id {
-                bail!("Impossible de supprimer un programme en cours d'exécution");
-            }
+        if let Some(ref state) = *active
+            && state.program_id == program_id {
+            bail!("Impossible de supprimer un programme en cours d'exécution");
         }
 
         let mut programs = self.programs.lock().unwrap();
-        if programs.remove(program_id).is_some() {
-            println!("🗑️  Programme '{}' supprimé", program_id);
-            Ok(())
-        } else {
-            bail!("Programme '{}' introuvable", program_id);
+        match programs.get(program_id) {
+            None => bail!("Programme '{}' introuvable", program_id),
+            Some(existing) if existing.builtin => {
+                bail!("Le programme intégré '{}' ne peut pas être supprimé", program_id)
+            }
+            Some(_) => {}
         }
+
+        storage::remove_file(&self.program_path(program_id))?;
+        programs.remove(program_id);
+        println!("🗑️  Programme '{}' supprimé", program_id);
+        Ok(())
+    }
+
+    fn program_path(&self, program_id: &str) -> PathBuf {
+        self.programs_dir.join(format!("{}.json", program_id))
+    }
+
+    /// Copie un programme (intégré ou non) en un nouveau programme modifiable.
+    /// Sans `new_id`, l'ID est dérivé de celui du programme source.
+    pub async fn clone_program(&self, program_id: &str, new_id: Option<String>,
+                               new_name: Option<String>) -> Result<TrainingProgram> {
+        let mut program = self.get_program(program_id).await
+            .ok_or_else(|| anyhow::anyhow!("Programme '{}' introuvable", program_id))?;
+
+        program.id = new_id.unwrap_or_else(|| self.unique_program_id(&format!("{}-copie", program_id)));
+        program.name = new_name.unwrap_or_else(|| format!("{} (copie)", program.name));
+        program.builtin = false;
+
+        self.create_program(program.clone()).await?;
+        Ok(program)
+    }
+
+    /// Importe un fichier d'entraînement comme nouveau programme.
+    /// `name` remplace le nom lu dans le fichier ; sans `id`, l'ID est dérivé du nom du programme.
+    pub async fn import_program(&self, format: ProgramFormat, source: &str,
+                                id: Option<String>, name: Option<String>) -> Result<Imported> {
+        let mut imported = formats::import(format, source, self.ftp, &self.power_limits())?;
+        if let Some(name) = name {
+            imported.program.id = formats::slugify(&name);
+            imported.program.name = name;
+        }
+        imported.program.id = id.unwrap_or_else(|| self.unique_program_id(&imported.program.id));
+
+        self.create_program(imported.program.clone()).await?;
+        println!("📥 Programme '{}' importé depuis un fichier {} ({} avertissement(s))",
+                 imported.program.id, format, imported.warnings.len());
+        Ok(imported)
+    }
+
+    /// Exporte un programme au format `format`
+    pub async fn export_program(&self, program_id: &str, format: ProgramFormat) -> Result<String> {
+        let program = self.get_program(program_id).await
+            .ok_or_else(|| anyhow::anyhow!("Programme '{}' introuvable", program_id))?;
+        formats::export(format, &program, self.ftp)
+    }
+
+    /// Charge prévue d'un programme, d'après la FTP configurée
+    pub async fn program_metrics(&self, program_id: &str) -> Option<ProgramMetrics> {
+        self.get_program(program_id).await.map(|program| program.metrics(self.ftp))
+    }
+
+    /// Liste tous les programmes avec leur charge prévue (même ordre que `list_programs`)
+    pub async fn list_programs_with_metrics(&self) -> Vec<ProgramWithMetrics> {
+        self.list_programs().await.into_iter()
+            .map(|program| ProgramWithMetrics { metrics: program.metrics(self.ftp), program })
+            .collect()
+    }
+
+    /// Premier ID libre parmi `base`, `base-2`, `base-3`...
+    fn unique_program_id(&self, base: &str) -> String {
+        let programs = self.programs.lock().unwrap();
+        (1..).map(|n| if n == 1 { base.to_string() } else { format!("{}-{}", base, n) })
+            .find(|id| !programs.contains_key(id))
+            .unwrap()
     }
 
-    /// Liste tous les programmes
+    /// Liste tous les programmes (programmes intégrés d'abord, puis par nom)
     pub async fn list_programs(&self) -> Vec<TrainingProgram> {
         let programs = self.programs.lock().unwrap();
-        programs.values().cloned().collect()
+        let mut list: Vec<TrainingProgram> = programs.values().cloned().collect();
+        list.sort_by(|a, b| b.builtin.cmp(&a.builtin).then_with(|| a.name.cmp(&b.name)));
+        list
     }
 
     /// Obtient un programme par son ID
@@ -359,19 +746,21 @@ impl BikeController {
                 .ok_or_else(|| anyhow::anyhow!("Programme '{}' introuvable", program_id))?
         };
 
-        if !program.is_valid() {
-            bail!("Programme invalide");
+        let errors = program.validate(&self.power_limits(), self.get_brake_level_range().await);
+        if !errors.is_empty() {
+            return Err(ValidationErrors(errors).into());
         }
 
         // Créer l'état d'exécution
-        let state = ProgramExecutionState::new(program);
+        let mut state = ProgramExecutionState::new(program);
 
-        // Définir la puissance initiale
-        self.set_power(state.current_power_target).await?;
+        // Appliquer la consigne initiale
+        let target = state.update_effective_target(&self.power_limits());
+        self.apply_target(target).await?;
 
         println!("🎯 Démarrage du programme : {}", state.program_name);
         println!("   Durée totale : {}s", state.total_duration);
-        println!("   Premier intervalle : {}W", state.current_power_target);
+        println!("   Premier intervalle : {}", target);
 
         *self.active_program.lock().unwrap() = Some(state);
 
@@ -391,16 +780,147 @@ impl BikeController {
 
         if let Some(state) = active.take() {
             println!("⏹️  Programme '{}' arrêté", state.program_name);
-            println!("   Progression : {:.1}% ({}/{}s)",
+            println!("   Progression : {:.1}% ({}/{}s, {}s restantes)",
                      state.progress_percentage(),
                      state.total_elapsed,
-                     state.total_duration);
+                     state.total_duration,
+                     state.remaining_time());
+            println!("   Puissance réelle moyenne : {:.0}W, travail : {:.1} kJ",
+                     state.average_power_actual,
+                     state.work_kj);
             Ok(())
         } else {
             bail!("Aucun programme en cours d'exécution");
         }
     }
 
+    /// Met le programme en cours en pause, en baissant éventuellement la puissance.
+    /// `recovery_power` remplace la puissance de récupération configurée.
+    pub async fn pause_program(&self, recovery_power: Option<u16>) -> Result<()> {
+        let recovery_power = recovery_power.or(self.pause_power);
+        if let Some(watts) = recovery_power {
+            let limits = self.power_limits();
+            if !limits.contains(watts) {
+                bail!("Puissance de récupération hors plage ({}-{})", limits.min, limits.max);
+            }
+        }
+
+        {
+            let mut active = self.active_program.lock().unwrap();
+            let Some(ref mut state) = *active else {
+                bail!("Aucun programme en cours d'exécution");
+            };
+
+            if !state.pause(unix_millis(), recovery_power) {
+                bail!("Le programme est déjà en pause");
+            }
+
+            println!("⏸️  Programme '{}' en pause ({}s écoulées)", state.program_name, state.total_elapsed);
+        }
+
+        if let Some(watts) = recovery_power {
+            self.set_power(watts).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reprend le programme en pause et réapplique la consigne de l'intervalle
+    pub async fn resume_program(&self) -> Result<()> {
+        let target = {
+            let mut active = self.active_program.lock().unwrap();
+            let Some(ref mut state) = *active else {
+                bail!("Aucun programme en cours d'exécution");
+            };
+
+            if !state.resume() {
+                bail!("Le programme n'est pas en pause");
+            }
+
+            println!("▶️  Reprise du programme '{}' ({}s de pause au total)",
+                     state.program_name, state.paused_secs);
+            state.update_effective_target(&self.power_limits())
+        };
+
+        self.apply_target(target).await
+    }
+
+    /// Se déplace dans le programme en cours et applique immédiatement la nouvelle consigne.
+    /// `offset_secs` n'est utilisé que pour `SeekAction::Offset`.
+    pub async fn seek_program(&self, action: SeekAction, offset_secs: u32) -> Result<ProgramExecutionState> {
+        let (state, target) = {
+            let mut active = self.active_program.lock().unwrap();
+            let Some(ref mut state) = *active else {
+                bail!("Aucun programme en cours d'exécution");
+            };
+
+            let index = state.current_interval_index;
+            let offset = match action {
+                SeekAction::Next => state.interval_start(index + 1)
+                    .ok_or_else(|| anyhow::anyhow!("Déjà au dernier intervalle"))?,
+                SeekAction::Previous => state.interval_start(index.saturating_sub(1)).unwrap_or(0),
+                SeekAction::Restart => state.interval_start(index).unwrap_or(0),
+                SeekAction::Offset => offset_secs,
+            };
+
+            if !state.seek(action, offset, unix_millis()) {
+                bail!("Position {}s au-delà de la fin du programme ({}s)", offset, state.total_duration);
+            }
+
+            let target = state.update_effective_target(&self.power_limits());
+            println!("⏩ Programme '{}' : {:?} → {}s, intervalle {} ({})",
+                     state.program_name, action, offset,
+                     state.current_interval_index + 1, target);
+            (state.clone(), target)
+        };
+
+        // En pause, la consigne sera appliquée à la reprise
+        if !state.paused {
+            self.apply_target(target).await?;
+        }
+
+        Ok(state)
+    }
+
+    /// Règle l'intensité du programme en cours (décalage en watts et/ou pourcentage)
+    /// et applique immédiatement la consigne effective
+    pub async fn set_program_intensity(&self, power_offset: Option<i16>,
+                                       intensity_percent: Option<u16>) -> Result<ProgramExecutionState> {
+        if let Some(offset) = power_offset
+            && offset.unsigned_abs() > POWER_OFFSET_MAX.unsigned_abs() {
+            bail!("Décalage de puissance hors plage (-{}W à +{}W)", POWER_OFFSET_MAX, POWER_OFFSET_MAX);
+        }
+        if let Some(percent) = intensity_percent
+            && !INTENSITY_PERCENT_RANGE.contains(&percent) {
+            bail!("Intensité hors plage ({}-{}%)", INTENSITY_PERCENT_RANGE.start(), INTENSITY_PERCENT_RANGE.end());
+        }
+
+        let (state, target) = {
+            let mut active = self.active_program.lock().unwrap();
+            let Some(ref mut state) = *active else {
+                bail!("Aucun programme en cours d'exécution");
+            };
+
+            if let Some(offset) = power_offset {
+                state.power_offset = offset;
+            }
+            if let Some(percent) = intensity_percent {
+                state.intensity_percent = percent;
+            }
+
+            let target = state.update_effective_target(&self.power_limits());
+            println!("🎚️  Intensité : {}% {:+}W → {}", state.intensity_percent, state.power_offset, target);
+            (state.clone(), target)
+        };
+
+        // En pause, la consigne sera appliquée à la reprise
+        if !state.paused {
+            self.apply_target(target).await?;
+        }
+
+        Ok(state)
+    }
+
     /// Obtient l'état du programme en cours
     pub async fn get_active_program(&self) -> Option<ProgramExecutionState> {
         self.active_program.lock().unwrap().clone()
@@ -413,34 +933,36 @@ impl BikeController {
         loop {
             interval.tick().await;
 
+            let mut pending_target = None;
             let should_stop = {
                 let mut active = self.active_program.lock().unwrap();
 
                 if let Some(ref mut state) = *active {
-                    // Avancer d'une seconde
-                    let finished = state.advance(1);
-
-                    if finished {
-                        println!("🏁 Programme '{}' terminé !", state.program_name);
-                        true
-                    } else {
-                        // Mettre à jour la puissance si on a changé d'intervalle
-                        let current_power = self.data.lock().unwrap().power;
-                        if current_power != state.current_power_target {
-                            println!("🔄 Changement d'intervalle : {}W → {}W",
-                                     current_power, state.current_power_target);
-                            if let Some(ref name) = state.current_interval_name {
-                                println!("   Intervalle : {}", name);
+                    let data = self.data.lock().unwrap().clone();
+
+                    // Pause automatique quand le cycliste arrête de pédaler (cadence fiable seulement si connecté)
+                    let auto_pause = state.program.auto_pause.map_or(self.auto_pause, |o| o.apply(self.auto_pause));
+                    if data.connected {
+                        match state.update_auto_pause(data.rpm, 1, &auto_pause, unix_millis(), self.pause_power) {
+                            Some(AutoPauseEvent::Paused) => {
+                                println!("⏸️  Pause automatique de '{}' (cadence {} tr/min)", state.program_name, data.rpm);
+                                pending_target = self.pause_power
+                                    .map(|power_target| IntervalTarget::Power { power_target });
                             }
-
-                            // Mettre à jour la puissance de manière asynchrone
-                            let controller = self.clone();
-                            let power = state.current_power_target;
-                            tokio::spawn(async move {
-                                let _ = controller.set_power(power).await;
-                            });
+                            Some(AutoPauseEvent::Resumed) => {
+                                println!("▶️  Reprise automatique de '{}'", state.program_name);
+                                pending_target = Some(state.update_effective_target(&self.power_limits()));
+                            }
+                            None => {}
                         }
+                    }
+
+                    if state.paused {
+                        // En pause : le programme n'avance plus et la consigne n'est pas réappliquée
+                        state.tick_paused(1);
                         false
+                    } else {
+                        self.advance_program(state, &data, &mut pending_target)
                     }
                 } else {
                     // Pas de programme actif, arrêter la boucle
@@ -453,6 +975,104 @@ impl BikeController {
                 *active = None;
                 break;
             }
+
+            // Consigne envoyée dans l'ordre par le thread d'E/S, hors du verrou
+            if let Some(target) = pending_target
+                && let Err(e) = self.apply_target(target).await {
+                eprintln!("⚠️  Impossible d'appliquer la consigne {} : {:#}", target, e);
+            }
         }
     }
+
+    /// Avance le programme d'une seconde et détermine la consigne à appliquer.
+    /// Retourne true si le programme est terminé.
+    fn advance_program(&self, state: &mut ProgramExecutionState, data: &BikeData,
+                       pending_target: &mut Option<IntervalTarget>) -> bool {
+        // Statistiques de séance basées sur la puissance réellement produite
+        if let Some(watts) = data.power_actual {
+            state.record_power_actual(watts, 1);
+        }
+        if data.connected {
+            state.record_cadence(data.rpm, 1);
+        }
+
+        // Avancer d'une seconde
+        let previous_index = state.current_interval_index;
+        let finished = state.advance(1);
+        if state.current_interval_index != previous_index
+            && let Some(compliance) = state.cadence_compliance_for(previous_index) {
+            println!("📊 Cadence {} respectée {:.0}% du temps ({}s au-dessus, {}s en dessous)",
+                     compliance.target, compliance.compliance_percent,
+                     compliance.above_secs, compliance.below_secs);
+        }
+        if finished {
+            println!("🏁 Programme '{}' terminé !", state.program_name);
+            println!("   Puissance réelle moyenne : {:.0}W, travail : {:.1} kJ",
+                     state.average_power_actual,
+                     state.work_kj);
+            return true;
+        }
+
+        // Mettre à jour la consigne si on a changé d'intervalle ou de palier de rampe
+        // (comparaison avec la consigne, pas avec la puissance réelle)
+        let target = state.update_effective_target(&self.power_limits());
+        let applied = match target {
+            IntervalTarget::Power { power_target } =>
+                data.power_target == power_target
+                    && data.brake_mode != Some(BrakeMode::ConstantBrake),
+            IntervalTarget::BrakeLevel { brake_level } =>
+                data.brake_level == Some(brake_level)
+                    && data.brake_mode == Some(BrakeMode::ConstantBrake),
+            // Consigne effective toujours résolue en puissance
+            IntervalTarget::Ramp { .. } => false,
+        };
+
+        if !applied && pending_target.is_none() {
+            if state.current_interval_index != previous_index {
+                println!("🔄 Changement d'intervalle : {}", state.current_interval_target);
+                if let Some(ref name) = state.current_interval_name {
+                    println!("   Intervalle : {}", name);
+                }
+                if let Some(cadence) = state.current_cadence_target {
+                    println!("   Cadence : {}", cadence);
+                }
+            }
+
+            *pending_target = Some(target);
+        }
+        false
+    }
+}
+
+/// Charge les programmes enregistrés. Les fichiers illisibles sont ignorés (et signalés).
+fn load_programs(dir: &Path) -> HashMap<String, TrainingProgram> {
+    let files = storage::list_json_files(dir).unwrap_or_else(|e| {
+        eprintln!("⚠️  Programmes enregistrés illisibles : {:?}", e);
+        Vec::new()
+    });
+
+    let mut programs = HashMap::new();
+    for path in files {
+        match storage::read_json::<TrainingProgram>(&path) {
+            Ok(Some(program)) => {
+                // L'ID doit correspondre au fichier, sinon l'écriture et la suppression viseraient un autre chemin
+                let stem = path.file_stem().and_then(|s| s.to_str());
+                if !is_valid_program_id(&program.id) || stem != Some(program.id.as_str()) {
+                    eprintln!("⚠️  Programme ignoré : ID '{}' invalide ou différent du fichier {:?}", program.id, path);
+                    continue;
+                }
+                programs.insert(program.id.clone(), program);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️  Programme ignoré : {:?}", e),
+        }
+    }
+    programs
+}
+
+/// Instant courant en millisecondes depuis l'epoch Unix
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
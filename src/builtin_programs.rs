@@ -0,0 +1,100 @@
+//! Programmes intégrés de 30 minutes (mêmes profils que l'interface `index.html`).
+//!
+//! Ils sont fournis par le serveur, en lecture seule : pour les modifier, il faut les cloner.
+
+use crate::training_program::{IntervalTarget, ProgramStep, TrainingInterval, TrainingProgram};
+
+/// (id, nom, description, puissance de chaque minute en watts)
+const BUILTIN_PROFILES: &[(&str, &str, &str, [u16; 30])] = &[
+    ("plat", "Plat", "Puissance constante et légère", [25; 30]),
+    ("escalier", "Escalier", "Progression de puissance par paliers", [
+        25, 25, 25, 35, 35, 35, 35, 35, 35, 35,
+        50, 50, 50, 50, 50, 50, 50, 50, 50, 50,
+        35, 35, 35, 35, 35, 35, 25, 25, 25, 25,
+    ]),
+    ("vallee", "Vallée", "Variations douces", [
+        25, 25, 25, 70, 90, 110, 120, 130, 120, 110,
+        80, 70, 90, 110, 130, 140, 130, 110, 90, 100,
+        120, 110, 100, 90, 80, 70, 60, 25, 25, 25,
+    ]),
+    ("collines", "Collines", "Deux collines distinctes", [
+        25, 25, 25, 50, 70, 90, 110, 130, 120, 100,
+        80, 60, 40, 30, 50, 70, 90, 110, 130, 120,
+        100, 80, 60, 55, 50, 45, 40, 25, 25, 25,
+    ]),
+    ("montagne", "Montagne", "Deux sommets", [
+        25, 25, 25, 80, 120, 140, 160, 150, 130, 110,
+        90, 80, 100, 120, 140, 160, 180, 160, 140, 120,
+        100, 90, 80, 70, 60, 60, 60, 25, 25, 25,
+    ]),
+    ("col-alpin", "Col Alpin", "Longue montée progressive", [
+        25, 25, 25, 50, 60, 70, 80, 90, 100, 110,
+        120, 130, 140, 150, 160, 160, 150, 130, 110, 90,
+        70, 60, 50, 45, 40, 35, 30, 25, 25, 25,
+    ]),
+    ("intervalle", "Intervalle", "Intervalles intenses", [
+        25, 25, 25, 80, 50, 80, 50, 90, 50, 110,
+        50, 110, 60, 125, 60, 125, 50, 110, 50, 90,
+        50, 100, 50, 90, 50, 70, 50, 25, 25, 25,
+    ]),
+    ("pyramide", "Pyramide", "Montée et descente symétriques", [
+        25, 25, 25, 40, 50, 60, 70, 80, 90, 100,
+        110, 115, 120, 120, 120, 115, 110, 100, 90, 80,
+        70, 60, 50, 40, 30, 25, 25, 25, 25, 25,
+    ]),
+];
+
+/// Construit les programmes intégrés
+pub fn builtin_programs() -> Vec<TrainingProgram> {
+    BUILTIN_PROFILES.iter()
+        .map(|(id, name, description, minutes)| TrainingProgram {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            intervals: minutes_to_intervals(minutes).into_iter().map(ProgramStep::from).collect(),
+            builtin: true,
+            auto_pause: None,
+        })
+        .collect()
+}
+
+/// Regroupe les minutes consécutives de même puissance en un seul intervalle
+fn minutes_to_intervals(minutes: &[u16]) -> Vec<TrainingInterval> {
+    let mut intervals: Vec<TrainingInterval> = Vec::new();
+
+    for &watts in minutes {
+        let target = IntervalTarget::Power { power_target: watts };
+        match intervals.last_mut() {
+            Some(last) if last.target == target => last.duration_secs += 60,
+            _ => intervals.push(TrainingInterval { duration_secs: 60, target, name: None, cadence: None }),
+        }
+    }
+
+    // Échauffement et retour au calme, quand le profil en comporte
+    if intervals.len() > 2 {
+        intervals[0].name = Some("Échauffement".to_string());
+        if let Some(last) = intervals.last_mut() {
+            last.name = Some("Retour au calme".to_string());
+        }
+    }
+
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::training_program::PowerLimits;
+
+    #[test]
+    fn test_builtin_programs_last_30_minutes() {
+        let programs = builtin_programs();
+        assert_eq!(programs.len(), 8);
+
+        for program in &programs {
+            assert!(program.builtin);
+            assert_eq!(program.total_duration(), 30 * 60, "{}", program.id);
+            assert!(program.validate(&PowerLimits::default(), None).is_empty(), "{}", program.id);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use anyhow::{Result, bail};
+use crate::connection::{BackoffPolicy, LivenessPolicy};
+use crate::trainer::BackendKind;
+use crate::training_program::{AutoPause, PowerLimits};
+
+/// Options acceptées sur la ligne de commande
+const OPTIONS: &[&str] = &[
+    "backend", "power-min", "power-max", "data-dir",
+    "backoff-initial", "backoff-max", "backoff-multiplier", "stale-timeout",
+    "pause-power", "auto-pause-rpm", "auto-pause-delay", "ftp",
+];
+
+/// FTP utilisée si aucune n'est configurée (en watts)
+const DEFAULT_FTP: u16 = 200;
+
+const USAGE: &str = "\
+Usage: elliptical_server [OPTIONS]
+
+Options :
+  --backend <kettler|mock>   Backend matériel (env : SKYLON_BACKEND)
+  --power-min <W>            Puissance minimale si l'appareil ne la rapporte pas,
+                             25 par défaut (env : SKYLON_POWER_MIN)
+  --power-max <W>            Puissance maximale si l'appareil ne la rapporte pas,
+                             400 par défaut (env : SKYLON_POWER_MAX)
+  --data-dir <chemin>        Répertoire des données persistantes,
+                             ./data par défaut (env : SKYLON_DATA_DIR)
+  --backoff-initial <s>      Délai avant la première reconnexion,
+                             2 par défaut (env : SKYLON_BACKOFF_INITIAL)
+  --backoff-max <s>          Délai maximal entre deux tentatives,
+                             60 par défaut (env : SKYLON_BACKOFF_MAX)
+  --backoff-multiplier <x>   Facteur d'augmentation du délai après chaque échec,
+                             2 par défaut (env : SKYLON_BACKOFF_MULTIPLIER)
+  --stale-timeout <s>        Durée sans progression des mesures avant de fermer
+                             la connexion, 15 par défaut (env : SKYLON_STALE_TIMEOUT)
+  --pause-power <W>          Puissance de récupération pendant la pause d'un programme,
+                             consigne inchangée par défaut (env : SKYLON_PAUSE_POWER)
+  --auto-pause-rpm <tr/min>  Met les programmes en pause quand la cadence reste sous ce
+                             seuil, désactivé par défaut (env : SKYLON_AUTO_PAUSE_RPM)
+  --auto-pause-delay <s>     Durée sous le seuil avant la pause automatique,
+                             5 par défaut (env : SKYLON_AUTO_PAUSE_DELAY)
+  --ftp <W>                  FTP du cycliste, pour convertir les puissances relatives
+                             des fichiers importés, 200 par défaut (env : SKYLON_FTP)
+  -h, --help                 Affiche cette aide";
+
+/// Configuration du serveur, lue depuis la ligne de commande puis les variables d'environnement
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Backend matériel utilisé par le contrôleur
+    pub backend: BackendKind,
+    /// Plage de puissance utilisée tant que l'appareil ne rapporte pas la sienne
+    pub fallback_power_limits: PowerLimits,
+    /// Répertoire des données persistantes (appareil préféré, programmes...)
+    pub data_dir: PathBuf,
+    /// Délais entre tentatives de (re)connexion
+    pub backoff: BackoffPolicy,
+    /// Détection des connexions inactives
+    pub liveness: LivenessPolicy,
+    /// Puissance de récupération appliquée pendant la pause d'un programme
+    pub pause_power: Option<u16>,
+    /// Pause automatique des programmes qui n'ont pas leur propre réglage
+    pub auto_pause: AutoPause,
+    /// FTP du cycliste en watts (puissances relatives des fichiers importés)
+    pub ftp: u16,
+}
+
+impl Config {
+    /// Construit la configuration à partir des arguments du processus et de l'environnement
+    pub fn load() -> Result<Self> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let options = parse_args(&args)?;
+        let value = |name: &str, env_key: &str| {
+            options.get(name).cloned().or_else(|| std::env::var(env_key).ok())
+        };
+
+        let backend = match value("backend", "SKYLON_BACKEND") {
+            Some(name) => name.parse()?,
+            None => BackendKind::default(),
+        };
+
+        let defaults = PowerLimits::default();
+        let fallback_power_limits = PowerLimits {
+            min: parse_value(value("power-min", "SKYLON_POWER_MIN"), "power-min")?.unwrap_or(defaults.min),
+            max: parse_value(value("power-max", "SKYLON_POWER_MAX"), "power-max")?.unwrap_or(defaults.max),
+        };
+        if fallback_power_limits.min == 0 || fallback_power_limits.min > fallback_power_limits.max {
+            bail!("Plage de puissance invalide ({}-{})", fallback_power_limits.min, fallback_power_limits.max);
+        }
+
+        let data_dir = value("data-dir", "SKYLON_DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("data"));
+
+        let default_backoff = BackoffPolicy::default();
+        let backoff = BackoffPolicy {
+            initial: parse_duration(value("backoff-initial", "SKYLON_BACKOFF_INITIAL"), "backoff-initial")?
+                .unwrap_or(default_backoff.initial),
+            max: parse_duration(value("backoff-max", "SKYLON_BACKOFF_MAX"), "backoff-max")?
+                .unwrap_or(default_backoff.max),
+            multiplier: parse_value(value("backoff-multiplier", "SKYLON_BACKOFF_MULTIPLIER"), "backoff-multiplier")?
+                .unwrap_or(default_backoff.multiplier),
+        };
+        if backoff.initial.is_zero() || backoff.initial > backoff.max || backoff.multiplier < 1.0 {
+            bail!("Paramètres de backoff invalides (initial > 0, initial <= max, multiplicateur >= 1)");
+        }
+
+        let default_liveness = LivenessPolicy::default();
+        let liveness = LivenessPolicy {
+            stale_after: parse_duration(value("stale-timeout", "SKYLON_STALE_TIMEOUT"), "stale-timeout")?
+                .unwrap_or(default_liveness.stale_after),
+            ..default_liveness
+        };
+        if liveness.stale_after.is_zero() {
+            bail!("Délai de détection d'inactivité invalide (doit être > 0)");
+        }
+
+        let pause_power: Option<u16> = parse_value(value("pause-power", "SKYLON_PAUSE_POWER"), "pause-power")?;
+        if let Some(watts) = pause_power
+            && (watts < fallback_power_limits.min || watts > fallback_power_limits.max)
+        {
+            bail!(
+                "Puissance de pause invalide ({}W, doit être entre {}W et {}W)",
+                watts, fallback_power_limits.min, fallback_power_limits.max
+            );
+        }
+
+        let default_auto_pause = AutoPause::default();
+        let auto_pause_rpm: Option<u16> = parse_value(value("auto-pause-rpm", "SKYLON_AUTO_PAUSE_RPM"), "auto-pause-rpm")?;
+        let auto_pause = AutoPause {
+            enabled: auto_pause_rpm.is_some(),
+            rpm_threshold: auto_pause_rpm.unwrap_or(default_auto_pause.rpm_threshold),
+            delay_secs: parse_value(value("auto-pause-delay", "SKYLON_AUTO_PAUSE_DELAY"), "auto-pause-delay")?
+                .unwrap_or(default_auto_pause.delay_secs),
+        };
+
+        let ftp = parse_value(value("ftp", "SKYLON_FTP"), "ftp")?.unwrap_or(DEFAULT_FTP);
+        if ftp == 0 {
+            bail!("FTP invalide (doit être > 0)");
+        }
+
+        Ok(Config { backend, fallback_power_limits, data_dir, backoff, liveness, pause_power, auto_pause, ftp })
+    }
+}
+
+/// Convertit la valeur d'une option, si elle est présente
+fn parse_value<T: FromStr>(value: Option<String>, name: &str) -> Result<Option<T>>
+where
+    T::Err: Display,
+{
+    value
+        .map(|v| v.parse().map_err(|e| anyhow::anyhow!("Valeur invalide pour --{} ('{}') : {}", name, v, e)))
+        .transpose()
+}
+
+/// Lit une durée en secondes (décimales acceptées), refusant les valeurs négatives ou démesurées
+fn parse_duration(value: Option<String>, name: &str) -> Result<Option<Duration>> {
+    let Some(secs) = parse_value::<f64>(value.clone(), name)? else {
+        return Ok(None);
+    };
+    Duration::try_from_secs_f64(secs)
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("Valeur invalide pour --{} ('{}') : {}", name, value.unwrap_or_default(), e))
+}
+
+/// Découpe les arguments `--option valeur` / `--option=valeur`
+fn parse_args(args: &[String]) -> Result<HashMap<String, String>> {
+    let mut options = HashMap::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+
+        let Some(option) = arg.strip_prefix("--") else {
+            bail!("Argument inattendu '{}'\n\n{}", arg, USAGE);
+        };
+
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => {
+                let value = iter.next()
+                    .ok_or_else(|| anyhow::anyhow!("Valeur manquante pour --{}", option))?;
+                (option.to_string(), value.clone())
+            }
+        };
+
+        if !OPTIONS.contains(&name.as_str()) {
+            bail!("Option inconnue '--{}'\n\n{}", name, USAGE);
+        }
+
+        options.insert(name, value);
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,250 @@
+//! État de la connexion à l'appareil et politique de reconnexion.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use crate::trainer::{DeviceInfo, Telemetry};
+
+/// États de la connexion à l'appareil
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// Aucune connexion et aucune tentative prévue (déconnexion manuelle)
+    Idle,
+    /// Recherche des appareils à portée
+    Scanning,
+    /// Connexion à l'appareil choisi
+    Connecting,
+    /// Connecté, les mesures arrivent normalement
+    Connected,
+    /// Connecté, mais les lectures échouent
+    Degraded,
+    /// En attente avant la prochaine tentative de connexion
+    Backoff,
+}
+
+impl ConnectionState {
+    /// Une tentative de connexion est en cours
+    pub fn is_attempting(&self) -> bool {
+        matches!(self, ConnectionState::Scanning | ConnectionState::Connecting)
+    }
+}
+
+/// Délais entre tentatives de connexion : `initial * multiplier^(n-1)`, plafonné à `max`
+#[derive(Debug, Clone, Copy)]
+pub struct BackoffPolicy {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(2),
+            max: Duration::from_secs(60),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl BackoffPolicy {
+    /// Délai avant la tentative suivant `failures` échecs consécutifs (>= 1)
+    pub fn delay(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(32) as i32;
+        let secs = self.initial.as_secs_f64() * self.multiplier.powi(exponent);
+        Duration::from_secs_f64(secs.min(self.max.as_secs_f64()))
+    }
+}
+
+/// Critères de détection d'une connexion inactive (lien RFCOMM mort mais encore ouvert)
+#[derive(Debug, Clone, Copy)]
+pub struct LivenessPolicy {
+    /// Durée maximale pendant laquelle le temps de l'appareil peut rester figé alors que la cadence est non nulle
+    pub stale_after: Duration,
+    /// Fenêtre d'observation des échecs de lecture
+    pub failure_window: Duration,
+    /// Nombre d'échecs de lecture dans la fenêtre au-delà duquel la connexion est abandonnée
+    pub max_failures: usize,
+}
+
+impl Default for LivenessPolicy {
+    fn default() -> Self {
+        Self {
+            stale_after: Duration::from_secs(15),
+            failure_window: Duration::from_secs(10),
+            max_failures: 5,
+        }
+    }
+}
+
+/// Surveille les mesures successives pour détecter une connexion figée.
+///
+/// kdri met en cache les dernières valeurs reçues : un lien mort continue donc
+/// de renvoyer des mesures, mais le temps de séance de l'appareil n'avance plus.
+#[derive(Debug)]
+pub struct LivenessMonitor {
+    policy: LivenessPolicy,
+    last_time: Option<u16>,
+    frozen_since: Option<Instant>,
+    failures: VecDeque<Instant>,
+}
+
+impl LivenessMonitor {
+    pub fn new(policy: LivenessPolicy) -> Self {
+        Self {
+            policy,
+            last_time: None,
+            frozen_since: None,
+            failures: VecDeque::new(),
+        }
+    }
+
+    /// Oublie l'historique (nouvelle connexion)
+    pub fn reset(&mut self) {
+        self.last_time = None;
+        self.frozen_since = None;
+        self.failures.clear();
+    }
+
+    /// Analyse une lecture réussie. Retourne la raison si la connexion est jugée inactive.
+    pub fn observe(&mut self, telemetry: &Telemetry, now: Instant) -> Option<String> {
+        if telemetry.online == Some(false) {
+            return Some("l'appareil signale être hors ligne".to_string());
+        }
+
+        // Le temps de séance doit avancer tant que le cycliste pédale
+        let frozen = telemetry.rpm > 0
+            && telemetry.time.is_some()
+            && telemetry.time == self.last_time;
+        self.last_time = telemetry.time;
+
+        if !frozen {
+            self.frozen_since = None;
+            return None;
+        }
+
+        let since = *self.frozen_since.get_or_insert(now);
+        let frozen_for = now.duration_since(since);
+        if frozen_for >= self.policy.stale_after {
+            return Some(format!("temps de l'appareil figé depuis {}s malgré une cadence de {} tr/min",
+                                frozen_for.as_secs(), telemetry.rpm));
+        }
+        None
+    }
+
+    /// Enregistre un échec de lecture. Retourne la raison si la connexion est jugée inactive.
+    pub fn record_failure(&mut self, now: Instant) -> Option<String> {
+        self.failures.push_back(now);
+        while let Some(&oldest) = self.failures.front() {
+            if now.duration_since(oldest) > self.policy.failure_window {
+                self.failures.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.failures.len() >= self.policy.max_failures {
+            return Some(format!("{} échecs de lecture en moins de {}s",
+                                self.failures.len(), self.policy.failure_window.as_secs()));
+        }
+        None
+    }
+}
+
+/// État observable de la connexion (exposé par `GET /connection`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionInfo {
+    pub state: ConnectionState,
+    /// Dernière erreur de connexion ou de communication
+    pub last_error: Option<String>,
+    /// Nombre de tentatives de connexion échouées consécutives
+    pub attempts: u32,
+    /// Date de la prochaine tentative (millisecondes depuis l'epoch Unix)
+    pub next_retry_at: Option<u64>,
+    /// Appareil actuellement connecté
+    pub device: Option<DeviceInfo>,
+    /// Reconnexion automatique active (désactivée par une déconnexion manuelle)
+    pub auto_reconnect: bool,
+    /// Échéance de la prochaine tentative
+    #[serde(skip)]
+    pub retry_deadline: Option<Instant>,
+}
+
+impl ConnectionInfo {
+    pub fn new() -> Self {
+        Self {
+            state: ConnectionState::Idle,
+            last_error: None,
+            attempts: 0,
+            next_retry_at: None,
+            device: None,
+            auto_reconnect: true,
+            retry_deadline: None,
+        }
+    }
+
+    /// Programme la prochaine tentative dans `delay`
+    pub fn schedule_retry(&mut self, delay: Duration) {
+        self.state = ConnectionState::Backoff;
+        self.retry_deadline = Some(Instant::now() + delay);
+        self.next_retry_at = (SystemTime::now() + delay)
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_millis() as u64);
+    }
+
+    /// Annule toute tentative programmée
+    pub fn clear_retry(&mut self) {
+        self.retry_deadline = None;
+        self.next_retry_at = None;
+    }
+
+    /// Temps restant avant la prochaine tentative
+    pub fn next_retry_in(&self) -> Option<Duration> {
+        self.retry_deadline.map(|d| d.saturating_duration_since(Instant::now()))
+    }
+}
+
+impl Default for ConnectionInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_grows_and_caps() {
+        let policy = BackoffPolicy {
+            initial: Duration::from_secs(2),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_secs(2));
+        assert_eq!(policy.delay(2), Duration::from_secs(4));
+        assert_eq!(policy.delay(4), Duration::from_secs(16));
+        assert_eq!(policy.delay(5), Duration::from_secs(30));
+        assert_eq!(policy.delay(100), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_liveness_detects_frozen_device_time() {
+        let mut monitor = LivenessMonitor::new(LivenessPolicy::default());
+        let start = Instant::now();
+        let telemetry = Telemetry { rpm: 60, time: Some(120), ..Telemetry::default() };
+
+        assert!(monitor.observe(&telemetry, start).is_none());
+        assert!(monitor.observe(&telemetry, start + Duration::from_secs(5)).is_none());
+        assert!(monitor.observe(&telemetry, start + Duration::from_secs(20)).is_some());
+
+        // Cycliste arrêté : un temps figé est normal
+        monitor.reset();
+        let stopped = Telemetry { rpm: 0, time: Some(120), ..Telemetry::default() };
+        assert!(monitor.observe(&stopped, start).is_none());
+        assert!(monitor.observe(&stopped, start + Duration::from_secs(60)).is_none());
+    }
+}
@@ -0,0 +1,485 @@
+//! Syntaxe texte compacte pour saisir un programme rapidement.
+//!
+//! ```text
+//! 10m@100W "Échauffement"
+//! 5x(1m@250W 95rpm, 1m@120W) "Sprints"
+//! 5m 150->80W
+//! ```
+//!
+//! - durée : `1h`, `10m`, `30s` ou combinaison (`1m30s`) ;
+//! - palier : `@100W`, ou en pourcentage de la FTP `@75%` ;
+//! - rampe : `150->80W` (`/10s` pour changer de consigne toutes les 10 s) ;
+//! - niveau de frein : `@L8` ;
+//! - cadence : `90rpm` ou `85-95rpm`, nom entre guillemets ;
+//! - bloc répété : `5x( ... )` ;
+//! - les éléments sont séparés par des virgules ou des retours à la ligne, `#` commence un commentaire.
+
+use std::fmt;
+use anyhow::Result;
+use crate::training_program::{CadenceTarget, IntervalTarget, ProgramStep, RepeatBlock, TrainingInterval,
+                              TrainingProgram};
+use super::{Imported, slugify};
+
+/// Erreur de syntaxe, positionnée dans le texte (à partir de 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ligne {}, colonne {} : {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Convertit un texte en programme, les pourcentages étant calculés d'après `ftp`
+pub fn parse(source: &str, ftp: u16) -> Result<Imported> {
+    let mut parser = Parser { chars: source.chars().collect(), position: 0, ftp };
+    let intervals = parser.steps(false)?;
+    if intervals.is_empty() {
+        return Err(parser.error("programme vide").into());
+    }
+
+    let name = "Programme importé".to_string();
+    Ok(Imported {
+        program: TrainingProgram {
+            id: slugify(&name),
+            name,
+            description: None,
+            intervals,
+            builtin: false,
+            auto_pause: None,
+        },
+        warnings: Vec::new(),
+    })
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    ftp: u16,
+}
+
+/// Unité d'une puissance saisie
+#[derive(Clone, Copy, PartialEq)]
+enum PowerUnit {
+    Watts,
+    PercentFtp,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let matches = s.chars().enumerate().all(|(i, c)| self.chars.get(self.position + i) == Some(&c));
+        if matches {
+            self.position += s.chars().count();
+        }
+        matches
+    }
+
+    /// Espaces et tabulations (pas les retours à la ligne, qui séparent les éléments)
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\r')) {
+            self.position += 1;
+        }
+    }
+
+    /// Espaces, séparateurs et commentaires entre deux éléments
+    fn skip_separators(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                c if c.is_whitespace() || c == ',' => self.position += 1,
+                '#' => self.skip_comment(),
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.position += 1;
+        }
+    }
+
+    fn error_at(&self, position: usize, message: impl Into<String>) -> SyntaxError {
+        let before = &self.chars[..position.min(self.chars.len())];
+        let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+        let column = before.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+        SyntaxError { line, column, message: message.into() }
+    }
+
+    fn error(&self, message: impl Into<String>) -> SyntaxError {
+        self.error_at(self.position, message)
+    }
+
+    /// Description du caractère courant, pour les messages d'erreur
+    fn found(&self) -> String {
+        match self.peek() {
+            Some('\n') => "fin de ligne".to_string(),
+            Some(c) => format!("'{}'", c),
+            None => "fin du texte".to_string(),
+        }
+    }
+
+    /// Liste d'éléments, jusqu'à la fin du texte ou jusqu'à `)` dans un bloc
+    fn steps(&mut self, in_block: bool) -> Result<Vec<ProgramStep>, SyntaxError> {
+        let mut steps = Vec::new();
+        self.skip_separators();
+
+        loop {
+            match self.peek() {
+                None if in_block => return Err(self.error("')' attendue pour fermer le bloc")),
+                None => break,
+                Some(')') if in_block => break,
+                Some(')') => return Err(self.error("')' sans bloc ouvert")),
+                _ => {}
+            }
+
+            steps.push(self.step()?);
+
+            self.skip_spaces();
+            if self.peek() == Some('#') {
+                self.skip_comment();
+            }
+            match self.peek() {
+                Some(',' | '\n') | None => {}
+                Some(')') if in_block => {}
+                _ => return Err(self.error(format!("',' ou fin de ligne attendue, {} trouvé", self.found()))),
+            }
+            self.skip_separators();
+        }
+
+        Ok(steps)
+    }
+
+    fn step(&mut self) -> Result<ProgramStep, SyntaxError> {
+        let start = self.position;
+        let count = self.integer()?;
+
+        if self.eat('x') || self.eat('×') {
+            self.skip_spaces();
+            if !self.eat('(') {
+                return Err(self.error(format!("'(' attendue après {}x, {} trouvé", count, self.found())));
+            }
+            let intervals = self.steps(true)?;
+            if intervals.is_empty() {
+                return Err(self.error("bloc vide"));
+            }
+            self.position += 1; // ')'
+            let name = self.attributes(start, false)?.1;
+            return Ok(ProgramStep::Repeat(RepeatBlock { repeat: count, intervals, name }));
+        }
+
+        let duration_secs = self.duration(count)?;
+        self.skip_spaces();
+        self.eat('@');
+        self.skip_spaces();
+        let target = self.target()?;
+        let (cadence, name) = self.attributes(start, true)?;
+
+        Ok(ProgramStep::Interval(TrainingInterval { duration_secs, target, name, cadence }))
+    }
+
+    fn integer(&mut self) -> Result<u32, SyntaxError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.error(format!("nombre attendu, {} trouvé", self.found())));
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        digits.parse().map_err(|_| self.error_at(start, format!("nombre trop grand : {}", digits)))
+    }
+
+    /// Durée dont le premier nombre a déjà été lu (`10m`, `1m30s`, `1h5m`)
+    fn duration(&mut self, first: u32) -> Result<u32, SyntaxError> {
+        let mut total: u32 = 0;
+        let mut value = first;
+        loop {
+            let factor = match self.peek() {
+                Some('h') => 3600,
+                Some('m') => 60,
+                Some('s') => 1,
+                _ => return Err(self.error(format!("unité de durée attendue (h, m ou s), {} trouvé", self.found()))),
+            };
+            self.position += 1;
+            total = value.checked_mul(factor)
+                .and_then(|secs| total.checked_add(secs))
+                .ok_or_else(|| self.error("durée trop longue"))?;
+
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Ok(total);
+            }
+            value = self.integer()?;
+        }
+    }
+
+    /// Consigne : `100W`, `75%`, `150->80W` (avec `/10s` optionnel) ou `L8`
+    fn target(&mut self) -> Result<IntervalTarget, SyntaxError> {
+        if self.eat('L') || self.eat('l') {
+            let start = self.position;
+            let level = self.integer()?;
+            let brake_level = u8::try_from(level)
+                .map_err(|_| self.error_at(start, format!("niveau de frein trop grand : {}", level)))?;
+            return Ok(IntervalTarget::BrakeLevel { brake_level });
+        }
+
+        let start = self.position;
+        let (from, from_unit) = self.power()?;
+        self.skip_spaces();
+        if !(self.eat_str("->") || self.eat('→')) {
+            let unit = from_unit.ok_or_else(|| self.error(format!("unité attendue (W ou %), {} trouvé", self.found())))?;
+            return Ok(IntervalTarget::Power { power_target: self.watts(from, unit, start)? });
+        }
+
+        self.skip_spaces();
+        let end_start = self.position;
+        let (to, to_unit) = self.power()?;
+        let unit = to_unit.or(from_unit)
+            .ok_or_else(|| self.error(format!("unité attendue (W ou %), {} trouvé", self.found())))?;
+        let power_start = self.watts(from, from_unit.unwrap_or(unit), start)?;
+        let power_end = self.watts(to, unit, end_start)?;
+
+        let step_secs = if self.eat('/') {
+            let first = self.integer()?;
+            Some(self.duration(first)?)
+        } else {
+            None
+        };
+        Ok(IntervalTarget::Ramp { power_start, power_end, step_secs })
+    }
+
+    /// Puissance et son unité éventuelle
+    fn power(&mut self) -> Result<(u32, Option<PowerUnit>), SyntaxError> {
+        let value = self.integer()?;
+        let unit = if self.eat('W') || self.eat('w') {
+            Some(PowerUnit::Watts)
+        } else if self.eat('%') {
+            Some(PowerUnit::PercentFtp)
+        } else {
+            None
+        };
+        Ok((value, unit))
+    }
+
+    fn watts(&self, value: u32, unit: PowerUnit, position: usize) -> Result<u16, SyntaxError> {
+        let watts = match unit {
+            PowerUnit::Watts => value as u64,
+            PowerUnit::PercentFtp => (value as u64 * self.ftp as u64 + 50) / 100,
+        };
+        u16::try_from(watts).map_err(|_| self.error_at(position, format!("puissance trop grande : {}W", watts)))
+    }
+
+    /// Cadence (`90rpm`, `85-95rpm`) et nom (`"Sprint"`) après un élément, dans n'importe quel ordre.
+    /// Un bloc répété (`cadence_allowed` à faux) n'accepte qu'un nom.
+    fn attributes(&mut self, start: usize, cadence_allowed: bool)
+                  -> Result<(Option<CadenceTarget>, Option<String>), SyntaxError> {
+        let mut cadence = None;
+        let mut name = None;
+
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some('"') if name.is_none() => name = Some(self.quoted()?),
+                Some(_) if self.at_cadence() && cadence.is_none() && cadence_allowed => {
+                    cadence = Some(self.cadence()?);
+                }
+                Some(_) if self.at_cadence() && !cadence_allowed => {
+                    return Err(self.error_at(start, "un bloc répété ne peut pas avoir de cadence"));
+                }
+                _ => return Ok((cadence, name)),
+            }
+        }
+    }
+
+    /// Le texte qui suit est-il une cadence (`90rpm`, `85-95rpm`) ? Sinon, il s'agit sans doute
+    /// d'un élément suivant auquel il manque la virgule.
+    fn at_cadence(&self) -> bool {
+        let rest = &self.chars[self.position..];
+        let digits = |from: usize| rest[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+        let mut end = digits(0);
+        if end > 0 && rest.get(end) == Some(&'-') {
+            end += 1 + digits(end + 1);
+        }
+        end > 0 && rest[end..].starts_with(&['r', 'p', 'm'])
+    }
+
+    fn cadence(&mut self) -> Result<CadenceTarget, SyntaxError> {
+        let min = self.rpm()?;
+        let cadence = if self.eat('-') {
+            CadenceTarget::Range { min, max: self.rpm()? }
+        } else {
+            CadenceTarget::Rpm(min)
+        };
+        if !self.eat_str("rpm") {
+            return Err(self.error(format!("'rpm' attendu après la cadence, {} trouvé", self.found())));
+        }
+        Ok(cadence)
+    }
+
+    fn rpm(&mut self) -> Result<u16, SyntaxError> {
+        let start = self.position;
+        let value = self.integer()?;
+        u16::try_from(value).map_err(|_| self.error_at(start, format!("cadence trop grande : {}", value)))
+    }
+
+    /// Texte entre guillemets (`\"`, `\\`, `\n`, `\r` et `\t` pour les caractères spéciaux)
+    fn quoted(&mut self) -> Result<String, SyntaxError> {
+        let start = self.position;
+        self.position += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.position += 1;
+                    return Ok(text);
+                }
+                Some('\\') if matches!(self.chars.get(self.position + 1), Some('"' | '\\' | 'n' | 'r' | 't')) => {
+                    text.push(match self.chars[self.position + 1] {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        c => c,
+                    });
+                    self.position += 2;
+                }
+                Some('\n') | None => return Err(self.error_at(start, "guillemet fermant manquant")),
+                Some(c) => {
+                    text.push(c);
+                    self.position += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Écriture canonique d'un programme : un élément de premier niveau par ligne
+pub fn write(program: &TrainingProgram) -> String {
+    let mut out = String::new();
+    for step in &program.intervals {
+        out.push_str(&write_step(step));
+        out.push('\n');
+    }
+    out
+}
+
+fn write_step(step: &ProgramStep) -> String {
+    match step {
+        ProgramStep::Interval(interval) => {
+            let mut text = format_duration(interval.duration_secs);
+            match interval.target {
+                IntervalTarget::Power { power_target } => text += &format!("@{}W", power_target),
+                IntervalTarget::BrakeLevel { brake_level } => text += &format!("@L{}", brake_level),
+                IntervalTarget::Ramp { power_start, power_end, step_secs } => {
+                    text += &format!(" {}->{}W", power_start, power_end);
+                    if let Some(step) = step_secs {
+                        text += &format!("/{}", format_duration(step));
+                    }
+                }
+            }
+            match interval.cadence {
+                Some(CadenceTarget::Rpm(rpm)) => text += &format!(" {}rpm", rpm),
+                Some(CadenceTarget::Range { min, max }) => text += &format!(" {}-{}rpm", min, max),
+                None => {}
+            }
+            text + &format_name(interval.name.as_deref())
+        }
+        ProgramStep::Repeat(block) => {
+            let inner: Vec<String> = block.intervals.iter().map(write_step).collect();
+            format!("{}x({}){}", block.repeat, inner.join(", "), format_name(block.name.as_deref()))
+        }
+    }
+}
+
+/// Durée avec les plus grandes unités possibles (`90` → `1m30s`)
+fn format_duration(secs: u32) -> String {
+    let mut text = String::new();
+    for (value, unit) in [(secs / 3600, "h"), (secs / 60 % 60, "m"), (secs % 60, "s")] {
+        if value > 0 {
+            text += &format!("{}{}", value, unit);
+        }
+    }
+    if text.is_empty() { "0s".to_string() } else { text }
+}
+
+fn format_name(name: Option<&str>) -> String {
+    match name {
+        Some(name) => {
+            let mut escaped = String::new();
+            for c in name.chars() {
+                match c {
+                    '\\' => escaped.push_str("\\\\"),
+                    '"' => escaped.push_str("\\\""),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    '\t' => escaped.push_str("\\t"),
+                    c => escaped.push(c),
+                }
+            }
+            format!(" \"{}\"", escaped)
+        }
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_write_dsl() {
+        let source = "10m@100W, 5x(1m@250W, 1m@120W), 5m 150->80W";
+        let program = parse(source, 200).unwrap().program;
+        assert_eq!(program.total_duration(), 600 + 5 * 120 + 300);
+        let ProgramStep::Interval(ramp) = &program.intervals[2] else { panic!() };
+        assert_eq!(ramp.target, IntervalTarget::Ramp { power_start: 150, power_end: 80, step_secs: None });
+
+        let source = "# Séance du mardi\n\
+                      1m30s @ 50% 85-95rpm \"Échauffement \\\"doux\\\"\"\n\
+                      3x( 2x(30s@300W 100rpm, 30s@L8), 1h5m 100->200W/10s ) \"Blocs\"";
+        let program = parse(source, 200).unwrap().program;
+        let canonical = write(&program);
+        assert_eq!(canonical, "1m30s@100W 85-95rpm \"Échauffement \\\"doux\\\"\"\n\
+                               3x(2x(30s@300W 100rpm, 30s@L8), 1h5m 100->200W/10s) \"Blocs\"\n");
+        let reparsed = parse(&canonical, 200).unwrap().program;
+        assert_eq!(serde_json::to_value(&reparsed).unwrap(), serde_json::to_value(&program).unwrap());
+
+        let error = |source: &str| parse(source, 200).unwrap_err().downcast::<SyntaxError>().unwrap();
+        assert_eq!(error("10m@100W,\n5m@").to_string(), "Ligne 2, colonne 4 : nombre attendu, fin du texte trouvé");
+        assert_eq!((error("10m@100W 5m@80W").line, error("10m@100W 5m@80W").column), (1, 10));
+        assert_eq!(error("2x(1m@100W").column, 11);
+        assert_eq!(error("10@100W").column, 3);
+    }
+
+    #[test]
+    fn test_write_escapes_names() {
+        // Les noms saisis via l'API JSON peuvent contenir des retours à la ligne
+        let mut program = parse("2x(30s@200W) \"Blocs\", 1m@100W", 200).unwrap().program;
+        let ProgramStep::Repeat(block) = &mut program.intervals[0] else { panic!() };
+        block.name = Some("Série\r\n\"A\"".to_string());
+        let ProgramStep::Interval(interval) = &mut program.intervals[1] else { panic!() };
+        interval.name = Some("Récup\\\tlibre\n".to_string());
+
+        let canonical = write(&program);
+        assert_eq!(canonical.lines().count(), 2);
+        let reparsed = parse(&canonical, 200).unwrap().program;
+        assert_eq!(serde_json::to_value(&reparsed).unwrap(), serde_json::to_value(&program).unwrap());
+    }
+}
@@ -0,0 +1,358 @@
+//! Fichiers ERG (watts) et MRC (pourcentage de la FTP).
+//!
+//! Le fichier décrit une courbe de puissance par points `minutes valeur` : deux points
+//! de même valeur forment un palier, deux valeurs différentes une rampe linéaire, et
+//! deux points au même instant un changement brusque de consigne.
+//!
+//! ```text
+//! [COURSE HEADER]
+//! VERSION = 2
+//! UNITS = ENGLISH
+//! FILE NAME = Sweet Spot
+//! MINUTES WATTS
+//! [END COURSE HEADER]
+//! [COURSE DATA]
+//! 0.00 100
+//! 10.00 200
+//! 10.00 220
+//! 18.00 220
+//! [END COURSE DATA]
+//! ```
+//!
+//! Les noms d'intervalles sont écrits dans `[COURSE TEXT]` (`secondes<TAB>message<TAB>durée`).
+
+use std::fmt::Write;
+use anyhow::{Result, anyhow, bail};
+use crate::training_program::{IntervalTarget, PowerLimits, ProgramStep, TrainingInterval, TrainingProgram};
+use super::{Imported, format_decimal, slugify};
+
+/// Durée d'affichage des messages écrits dans `[COURSE TEXT]` (en secondes)
+const TEXT_DURATION_SECS: u32 = 10;
+
+/// Unité des valeurs de `[COURSE DATA]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// Watts (ERG)
+    Watts,
+    /// Pourcentage de la FTP (MRC)
+    PercentFtp,
+}
+
+impl Unit {
+    fn header(&self) -> &'static str {
+        match self {
+            Unit::Watts => "MINUTES WATTS",
+            Unit::PercentFtp => "MINUTES PERCENT",
+        }
+    }
+}
+
+/// Section du fichier en cours de lecture
+#[derive(PartialEq)]
+enum Section {
+    None,
+    Header,
+    Data,
+    Text,
+}
+
+/// Convertit un fichier ERG ou MRC. L'unité annoncée dans l'en-tête (`MINUTES WATTS`,
+/// `MINUTES PERCENT`) prime sur `unit`, et la FTP de l'en-tête (`FTP = 250`) sur `ftp`.
+pub fn parse(source: &str, mut unit: Unit, mut ftp: u16, limits: &PowerLimits) -> Result<Imported> {
+    let mut section = Section::None;
+    let mut name = None;
+    let mut description = None;
+    let mut file_ftp = None;
+    let mut points: Vec<(u32, f64, usize)> = Vec::new();
+    let mut texts: Vec<(u32, String)> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        let upper = line.to_ascii_uppercase();
+        match upper.as_str() {
+            "[COURSE HEADER]" => { section = Section::Header; continue; }
+            "[COURSE DATA]" => { section = Section::Data; continue; }
+            "[COURSE TEXT]" => { section = Section::Text; continue; }
+            _ if upper.starts_with("[END") => { section = Section::None; continue; }
+            _ => {}
+        }
+
+        match section {
+            Section::Header => {
+                if upper.starts_with("MINUTES") {
+                    if upper.contains("PERCENT") {
+                        unit = Unit::PercentFtp;
+                    } else if upper.contains("WATTS") {
+                        unit = Unit::Watts;
+                    }
+                } else if let Some((key, value)) = line.split_once('=') {
+                    let value = value.trim();
+                    match key.trim().to_ascii_uppercase().as_str() {
+                        "FILE NAME" if !value.is_empty() => name = Some(value.to_string()),
+                        "DESCRIPTION" if !value.is_empty() => description = Some(value.to_string()),
+                        "FTP" => match value.parse::<u16>() {
+                            Ok(value) if value > 0 => file_ftp = Some(value),
+                            _ => warnings.push(format!("Ligne {} : FTP '{}' illisible, ignorée", line_number, value)),
+                        },
+                        _ => {}
+                    }
+                }
+            }
+            Section::Data => {
+                let mut columns = line.split_whitespace();
+                let (Some(minutes), Some(value)) = (columns.next(), columns.next()) else {
+                    bail!("Ligne {} : point incomplet '{}' (attendu : minutes valeur)", line_number, line);
+                };
+                let minutes = parse_number(minutes, line_number)?;
+                let value = parse_number(value, line_number)?;
+                if minutes < 0.0 || value < 0.0 {
+                    bail!("Ligne {} : valeur négative", line_number);
+                }
+                let secs = (minutes * 60.0).round() as u32;
+                if let Some(&(previous, _, _)) = points.last()
+                    && secs < previous {
+                    bail!("Ligne {} : le temps recule ({} min)", line_number, minutes);
+                }
+                points.push((secs, value, line_number));
+            }
+            Section::Text => {
+                let mut columns = line.splitn(3, '\t');
+                match (columns.next().map(|t| t.trim().parse::<f64>()), columns.next()) {
+                    (Some(Ok(secs)), Some(message)) if !message.trim().is_empty() => {
+                        texts.push((secs.max(0.0).round() as u32, message.trim().to_string()));
+                    }
+                    _ => warnings.push(format!("Ligne {} : message illisible, ignoré", line_number)),
+                }
+            }
+            Section::None => warnings.push(format!("Ligne {} : hors section, ignorée", line_number)),
+        }
+    }
+
+    // Les pourcentages se rapportent à la FTP avec laquelle le fichier a été écrit
+    if let Some(file_ftp) = file_ftp
+        && unit == Unit::PercentFtp {
+        if file_ftp != ftp {
+            warnings.push(format!("FTP du fichier ({}W) utilisée à la place de la FTP configurée ({}W)", file_ftp, ftp));
+        }
+        ftp = file_ftp;
+    }
+
+    let mut intervals: Vec<(u32, TrainingInterval)> = Vec::new();
+    for pair in points.windows(2) {
+        let (start, from, _) = pair[0];
+        let (end, to, line_number) = pair[1];
+        // Changement brusque de consigne
+        if end == start {
+            continue;
+        }
+
+        let watts = |value: f64| match unit {
+            Unit::Watts => value,
+            Unit::PercentFtp => value * ftp as f64 / 100.0,
+        }.round() as u32;
+        let clamp = |watts: u32| watts.clamp(limits.min as u32, limits.max as u32) as u16;
+        let (raw_start, raw_end) = (watts(from), watts(to));
+        let (power_start, power_end) = (clamp(raw_start), clamp(raw_end));
+        if (power_start as u32, power_end as u32) != (raw_start, raw_end) {
+            warnings.push(format!("Ligne {} : puissance ramenée dans la plage de l'appareil ({}-{}W)",
+                                  line_number, limits.min, limits.max));
+        }
+        let target = if power_start == power_end {
+            IntervalTarget::Power { power_target: power_start }
+        } else {
+            IntervalTarget::Ramp { power_start, power_end, step_secs: None }
+        };
+        intervals.push((start, TrainingInterval { duration_secs: end - start, target, name: None, cadence: None }));
+    }
+    if intervals.is_empty() {
+        bail!("Aucun segment dans [COURSE DATA] (au moins deux points sont nécessaires)");
+    }
+
+    // Chaque message nomme l'intervalle pendant lequel il s'affiche
+    for (secs, message) in texts {
+        let interval = intervals.iter_mut()
+            .find(|(start, interval)| secs >= *start && secs < start + interval.duration_secs);
+        match interval {
+            Some((_, interval)) if interval.name.is_none() => interval.name = Some(message),
+            Some(_) => warnings.push(format!("Message à {}s ignoré : l'intervalle a déjà un nom", secs)),
+            None => warnings.push(format!("Message à {}s ignoré : au-delà de la fin du programme", secs)),
+        }
+    }
+
+    let name = name.unwrap_or_else(|| "Programme importé".to_string());
+    Ok(Imported {
+        program: TrainingProgram {
+            id: slugify(&name),
+            name,
+            description,
+            intervals: intervals.into_iter().map(|(_, interval)| ProgramStep::from(interval)).collect(),
+            builtin: false,
+            auto_pause: None,
+        },
+        warnings,
+    })
+}
+
+fn parse_number(value: &str, line_number: usize) -> Result<f64> {
+    value.parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| anyhow!("Ligne {} : '{}' n'est pas un nombre", line_number, value))
+}
+
+/// Écrit le programme (répétitions déroulées) en ERG ou MRC. Les pourcentages sont
+/// calculés d'après `ftp`, indiquée dans l'en-tête.
+pub fn write(program: &TrainingProgram, unit: Unit, ftp: u16) -> Result<String> {
+    let value = |watts: u16| match unit {
+        Unit::Watts => watts.to_string(),
+        Unit::PercentFtp => format_decimal(watts as f64 * 100.0 / ftp as f64, 2),
+    };
+
+    let mut data = String::new();
+    let mut texts = String::new();
+    let mut start = 0;
+    for (index, flat) in program.flatten().iter().enumerate() {
+        let interval = &flat.interval;
+        let duration = interval.duration_secs;
+        // Segments (début, fin, puissance de départ, puissance d'arrivée) relatifs à l'intervalle
+        let segments = match interval.target {
+            IntervalTarget::Power { power_target } => vec![(0, duration, power_target, power_target)],
+            // Une rampe par paliers est écrite marche par marche, le format n'ayant que des rampes continues
+            IntervalTarget::Ramp { step_secs: Some(step), .. } if step > 1 => (0..duration)
+                .step_by(step as usize)
+                .filter_map(|from| {
+                    let watts = interval.target.power_at(from, duration)?;
+                    Some((from, (from + step).min(duration), watts, watts))
+                })
+                .collect(),
+            IntervalTarget::Ramp { power_start, power_end, .. } => vec![(0, duration, power_start, power_end)],
+            IntervalTarget::BrakeLevel { .. } => {
+                bail!("Intervalle {} en niveau de frein : non exportable (consignes en puissance uniquement)", index + 1)
+            }
+        };
+        for (from, to, from_watts, to_watts) in segments {
+            writeln!(data, "{}\t{}", format_minutes(start + from), value(from_watts))?;
+            writeln!(data, "{}\t{}", format_minutes(start + to), value(to_watts))?;
+        }
+        let end = start + duration;
+        if let Some(ref name) = interval.name {
+            writeln!(texts, "{}\t{}\t{}", start, single_line(name), TEXT_DURATION_SECS.min(interval.duration_secs))?;
+        }
+        start = end;
+    }
+
+    let mut out = String::new();
+    writeln!(out, "[COURSE HEADER]")?;
+    writeln!(out, "VERSION = 2")?;
+    writeln!(out, "UNITS = ENGLISH")?;
+    if let Some(ref description) = program.description {
+        writeln!(out, "DESCRIPTION = {}", single_line(description))?;
+    }
+    writeln!(out, "FILE NAME = {}", single_line(&program.name))?;
+    writeln!(out, "FTP = {}", ftp)?;
+    writeln!(out, "{}", unit.header())?;
+    writeln!(out, "[END COURSE HEADER]")?;
+    writeln!(out, "[COURSE DATA]")?;
+    out.push_str(&data);
+    writeln!(out, "[END COURSE DATA]")?;
+    if !texts.is_empty() {
+        writeln!(out, "[COURSE TEXT]")?;
+        out.push_str(&texts);
+        writeln!(out, "[END COURSE TEXT]")?;
+    }
+    Ok(out)
+}
+
+/// Texte sur une seule ligne, sans tabulation : le format n'a pas de caractère d'échappement
+fn single_line(text: &str) -> String {
+    text.replace(['\t', '\r', '\n'], " ")
+}
+
+/// Minutes avec 2 décimales : l'erreur (< 0,5 s) disparaît à l'arrondi à la seconde lors de la relecture
+fn format_minutes(secs: u32) -> String {
+    format!("{:.2}", secs as f64 / 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erg_mrc_round_trip() {
+        let source = "[COURSE HEADER]\r\nFILE NAME = Seuil\r\nMINUTES WATTS\r\n[END COURSE HEADER]\r\n\
+                      [COURSE DATA]\r\n0\t100\r\n10\t200\r\n10\t250\r\n12.5\t250\r\n12.5\t600\r\n13\t600\r\n[END COURSE DATA]\r\n\
+                      [COURSE TEXT]\r\n600\tSeuil\t10\r\n[END COURSE TEXT]\r\n";
+        let imported = parse(source, Unit::PercentFtp, 200, &PowerLimits::default()).unwrap();
+        let program = imported.program;
+        assert_eq!(program.id, "seuil");
+        assert_eq!(program.total_duration(), 13 * 60);
+        assert_eq!(imported.warnings.len(), 1, "{:?}", imported.warnings);
+
+        let ProgramStep::Interval(ramp) = &program.intervals[0] else { panic!() };
+        assert_eq!(ramp.target, IntervalTarget::Ramp { power_start: 100, power_end: 200, step_secs: None });
+        let ProgramStep::Interval(steady) = &program.intervals[1] else { panic!() };
+        assert_eq!((steady.duration_secs, steady.name.as_deref()), (150, Some("Seuil")));
+
+        // Les paliers, rampes et noms survivent à l'export puis à la relecture, en watts comme en % FTP
+        for (unit, ftp) in [(Unit::Watts, 200), (Unit::PercentFtp, 237)] {
+            let written = write(&program, unit, ftp).unwrap();
+            let reread = parse(&written, Unit::Watts, ftp, &PowerLimits::default()).unwrap();
+            assert!(reread.warnings.is_empty());
+            assert_eq!(serde_json::to_value(&reread.program).unwrap(), serde_json::to_value(&program).unwrap());
+        }
+
+        // Un MRC relu avec une autre FTP configurée garde les puissances de l'en-tête
+        let written = write(&program, Unit::PercentFtp, 237).unwrap();
+        let reread = parse(&written, Unit::PercentFtp, 300, &PowerLimits::default()).unwrap();
+        assert_eq!(reread.warnings.len(), 1, "{:?}", reread.warnings);
+        assert_eq!(serde_json::to_value(&reread.program).unwrap(), serde_json::to_value(&program).unwrap());
+    }
+
+    #[test]
+    fn test_write_stepped_ramp() {
+        // Rampe par paliers de 40 s sur 100 s : marches à 100, 150 puis 200 W
+        let json = r#"{ "id": "escalier", "name": "Escalier", "intervals": [
+            { "duration_secs": 100, "power_start": 100, "power_end": 200, "step_secs": 40, "name": "Montée" }
+        ] }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        let reread = parse(&write(&program, Unit::Watts, 200).unwrap(), Unit::Watts, 200, &PowerLimits::default()).unwrap();
+        let steps: Vec<_> = reread.program.intervals.iter()
+            .map(|step| match step {
+                ProgramStep::Interval(interval) => (interval.duration_secs, interval.target),
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(steps, [40, 40, 20].into_iter().zip([100, 150, 200])
+            .map(|(duration, power_target)| (duration, IntervalTarget::Power { power_target }))
+            .collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_write_sanitizes_names() {
+        // Noms saisis via l'API JSON : ni en-tête, ni colonne, ni point supplémentaire
+        let json = r#"{
+            "id": "seuil", "name": "Seuil\n[END COURSE HEADER]\nFTP = 1", "description": null,
+            "intervals": [
+                { "duration_secs": 120, "power_target": 150, "name": "Bloc\t1\n3.00\t400" },
+                { "duration_secs": 60, "power_target": 100 }
+            ]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        for unit in [Unit::Watts, Unit::PercentFtp] {
+            let written = write(&program, unit, 200).unwrap();
+            let reread = parse(&written, Unit::Watts, 200, &PowerLimits::default()).unwrap();
+            assert!(reread.warnings.is_empty(), "{:?}", reread.warnings);
+            assert_eq!(reread.program.name, "Seuil [END COURSE HEADER] FTP = 1");
+            assert_eq!(reread.program.total_duration(), 180);
+            let ProgramStep::Interval(first) = &reread.program.intervals[0] else { panic!() };
+            assert_eq!(first.name.as_deref(), Some("Bloc 1 3.00 400"));
+            assert_eq!(first.target, IntervalTarget::Power { power_target: 150 });
+        }
+    }
+}
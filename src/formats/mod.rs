@@ -0,0 +1,127 @@
+//! Import et export des fichiers d'entraînement d'autres logiciels.
+
+pub mod dsl;
+pub mod erg;
+pub mod zwo;
+
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{Result, bail};
+use serde::Serialize;
+use crate::training_program::{PowerLimits, TrainingProgram};
+
+/// Formats de fichiers d'entraînement reconnus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramFormat {
+    /// Entraînement Zwift (XML)
+    Zwo,
+    /// Courbe de puissance en watts
+    Erg,
+    /// Courbe de puissance en pourcentage de la FTP
+    Mrc,
+    /// Syntaxe texte compacte (voir [`dsl`])
+    Txt,
+}
+
+impl ProgramFormat {
+    /// Devine le format d'après le contenu du fichier
+    pub fn detect(source: &str) -> Option<Self> {
+        let upper = source.to_ascii_uppercase();
+        if source.contains("<workout_file") {
+            Some(ProgramFormat::Zwo)
+        } else if upper.contains("[COURSE DATA]") {
+            // L'en-tête indique l'unité ; ERG par défaut
+            if upper.contains("MINUTES PERCENT") { Some(ProgramFormat::Mrc) } else { Some(ProgramFormat::Erg) }
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for ProgramFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "zwo" => Ok(ProgramFormat::Zwo),
+            "erg" => Ok(ProgramFormat::Erg),
+            "mrc" => Ok(ProgramFormat::Mrc),
+            "txt" | "dsl" => Ok(ProgramFormat::Txt),
+            other => bail!("Format inconnu '{}' (valeurs possibles : zwo, erg, mrc, txt)", other),
+        }
+    }
+}
+
+impl fmt::Display for ProgramFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramFormat::Zwo => write!(f, "zwo"),
+            ProgramFormat::Erg => write!(f, "erg"),
+            ProgramFormat::Mrc => write!(f, "mrc"),
+            ProgramFormat::Txt => write!(f, "txt"),
+        }
+    }
+}
+
+/// Programme importé, avec les éléments du fichier ignorés ou convertis approximativement
+#[derive(Debug, Clone, Serialize)]
+pub struct Imported {
+    pub program: TrainingProgram,
+    pub warnings: Vec<String>,
+}
+
+/// Convertit un fichier au format `format`. Les puissances relatives sont calculées
+/// d'après `ftp` (en watts) puis ramenées dans `limits` (sauf pour le texte, saisi à la main
+/// et donc validé comme un programme créé depuis l'éditeur).
+pub fn import(format: ProgramFormat, source: &str, ftp: u16, limits: &PowerLimits) -> Result<Imported> {
+    match format {
+        ProgramFormat::Zwo => zwo::parse(source, ftp, limits),
+        ProgramFormat::Erg => erg::parse(source, erg::Unit::Watts, ftp, limits),
+        ProgramFormat::Mrc => erg::parse(source, erg::Unit::PercentFtp, ftp, limits),
+        ProgramFormat::Txt => dsl::parse(source, ftp),
+    }
+}
+
+/// Écrit le programme au format `format`, les puissances relatives étant calculées d'après `ftp`
+pub fn export(format: ProgramFormat, program: &TrainingProgram, ftp: u16) -> Result<String> {
+    match format {
+        ProgramFormat::Zwo => zwo::write(program, ftp),
+        ProgramFormat::Erg => erg::write(program, erg::Unit::Watts, ftp),
+        ProgramFormat::Mrc => erg::write(program, erg::Unit::PercentFtp, ftp),
+        ProgramFormat::Txt => Ok(dsl::write(program)),
+    }
+}
+
+/// Nombre avec au plus `decimals` décimales, sans zéros inutiles
+fn format_decimal(value: f64, decimals: usize) -> String {
+    let text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// Dérive un ID de programme (lettres, chiffres et '-') à partir d'un nom
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' => 'i',
+            'ô' | 'ö' => 'o',
+            'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            c => c,
+        };
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "programme".to_string() } else { slug.to_string() }
+}
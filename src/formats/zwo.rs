@@ -0,0 +1,427 @@
+//! Entraînements Zwift (`.zwo`), en lecture et en écriture.
+//!
+//! Un fichier ZWO décrit les blocs de l'entraînement dans `<workout>`, avec des
+//! puissances exprimées en fraction de la FTP :
+//!
+//! ```xml
+//! <workout_file>
+//!   <name>Sweet Spot</name>
+//!   <workout>
+//!     <Warmup Duration="600" PowerLow="0.4" PowerHigh="0.75"/>
+//!     <IntervalsT Repeat="3" OnDuration="480" OffDuration="120" OnPower="0.9" OffPower="0.5"/>
+//!     <Cooldown Duration="300" PowerLow="0.6" PowerHigh="0.3"/>
+//!   </workout>
+//! </workout_file>
+//! ```
+
+use std::fmt::Write;
+use anyhow::{Result, anyhow, bail};
+use roxmltree::{Document, Node};
+use crate::training_program::{CadenceTarget, IntervalTarget, PowerLimits, ProgramStep, RepeatBlock,
+                              TrainingInterval, TrainingProgram};
+use super::{Imported, format_decimal, slugify};
+
+/// Puissance (en fraction de la FTP) des blocs `FreeRide`, qui n'ont pas de consigne
+const FREE_RIDE_FTP_FRACTION: f64 = 0.5;
+
+/// Convertit un fichier ZWO
+pub fn parse(source: &str, ftp: u16, limits: &PowerLimits) -> Result<Imported> {
+    let document = Document::parse(source).map_err(|e| anyhow!("Fichier ZWO illisible : {}", e))?;
+    let root = document.root_element();
+    if !root.has_tag_name("workout_file") {
+        bail!("Fichier ZWO invalide : élément racine <{}> au lieu de <workout_file>", root.tag_name().name());
+    }
+
+    let name = child_text(root, "name").unwrap_or_else(|| "Programme importé".to_string());
+    let description = child_text(root, "description");
+    let workout = root.children()
+        .find(|n| n.has_tag_name("workout"))
+        .ok_or_else(|| anyhow!("Fichier ZWO invalide : élément <workout> absent"))?;
+
+    let mut converter = Converter { document: &document, ftp, limits, warnings: Vec::new() };
+    let mut intervals = Vec::new();
+    for element in workout.children().filter(|n| n.is_element()) {
+        if let Some(step) = converter.step(element)? {
+            intervals.push(step);
+        }
+    }
+    if intervals.is_empty() {
+        bail!("Aucun bloc pris en charge dans le fichier ZWO");
+    }
+
+    Ok(Imported {
+        program: TrainingProgram {
+            id: slugify(&name),
+            name,
+            description,
+            intervals,
+            builtin: false,
+            auto_pause: None,
+        },
+        warnings: converter.warnings,
+    })
+}
+
+/// Texte d'un élément enfant, s'il est présent et non vide
+fn child_text(node: Node, tag: &str) -> Option<String> {
+    node.children()
+        .find(|n| n.has_tag_name(tag))
+        .and_then(|n| n.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+struct Converter<'a, 'input> {
+    document: &'a Document<'input>,
+    ftp: u16,
+    limits: &'a PowerLimits,
+    warnings: Vec<String>,
+}
+
+impl Converter<'_, '_> {
+    /// Convertit un bloc de `<workout>` (`None` s'il est ignoré)
+    fn step(&mut self, node: Node) -> Result<Option<ProgramStep>> {
+        let tag = node.tag_name().name();
+        let name = self.text_events(node);
+
+        // La casse varie selon les éditeurs (`FreeRide`, `Freeride`...)
+        let step = match tag.to_ascii_lowercase().as_str() {
+            "steadystate" | "solidstate" => {
+                let power = match node.attribute("Power") {
+                    Some(_) => self.number(node, "Power")?,
+                    // Certains éditeurs écrivent un palier comme une rampe plate
+                    None => (self.number(node, "PowerLow")? + self.number(node, "PowerHigh")?) / 2.0,
+                };
+                ProgramStep::Interval(TrainingInterval {
+                    duration_secs: self.duration(node, "Duration")?,
+                    target: IntervalTarget::Power { power_target: self.watts(node, power) },
+                    name,
+                    cadence: self.cadence(node, "Cadence")?,
+                })
+            }
+            "warmup" | "cooldown" | "ramp" => {
+                let power_start = self.watts(node, self.number(node, "PowerLow")?);
+                let power_end = self.watts(node, self.number(node, "PowerHigh")?);
+                let target = if power_start == power_end {
+                    IntervalTarget::Power { power_target: power_start }
+                } else {
+                    IntervalTarget::Ramp { power_start, power_end, step_secs: None }
+                };
+                ProgramStep::Interval(TrainingInterval {
+                    duration_secs: self.duration(node, "Duration")?,
+                    target,
+                    name,
+                    cadence: self.cadence(node, "Cadence")?,
+                })
+            }
+            "intervalst" => {
+                let on = TrainingInterval {
+                    duration_secs: self.duration(node, "OnDuration")?,
+                    target: IntervalTarget::Power { power_target: self.watts(node, self.number(node, "OnPower")?) },
+                    name: None,
+                    cadence: self.cadence(node, "Cadence")?,
+                };
+                let off = TrainingInterval {
+                    duration_secs: self.duration(node, "OffDuration")?,
+                    target: IntervalTarget::Power { power_target: self.watts(node, self.number(node, "OffPower")?) },
+                    name: None,
+                    cadence: self.cadence(node, "CadenceResting")?,
+                };
+                let repeat = match node.attribute("Repeat") {
+                    Some(_) => self.number(node, "Repeat")?.round() as u32,
+                    None => 1,
+                };
+                ProgramStep::Repeat(RepeatBlock { repeat, intervals: vec![on.into(), off.into()], name })
+            }
+            "freeride" => {
+                let watts = self.watts(node, FREE_RIDE_FTP_FRACTION);
+                self.warn(node, format!("bloc libre sans consigne de puissance, converti en palier à {}W", watts));
+                ProgramStep::Interval(TrainingInterval {
+                    duration_secs: self.duration(node, "Duration")?,
+                    target: IntervalTarget::Power { power_target: watts },
+                    name: name.or_else(|| Some("Libre".to_string())),
+                    cadence: self.cadence(node, "Cadence")?,
+                })
+            }
+            _ => {
+                self.warn(node, "élément non pris en charge, ignoré".to_string());
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(step))
+    }
+
+    /// Nom du bloc tiré de ses messages (`<textevent message="..."/>`) : le premier est conservé
+    fn text_events(&mut self, node: Node) -> Option<String> {
+        let messages: Vec<&str> = node.children()
+            .filter(|n| n.tag_name().name().eq_ignore_ascii_case("textevent"))
+            .filter_map(|n| n.attribute("message"))
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .collect();
+
+        if messages.len() > 1 {
+            self.warn(node, format!("{} messages : seul le premier est conservé comme nom de l'intervalle",
+                                    messages.len()));
+        }
+        messages.first().map(|m| m.to_string())
+    }
+
+    fn duration(&self, node: Node, attribute: &str) -> Result<u32> {
+        let secs = self.number(node, attribute)?;
+        if secs < 0.0 {
+            bail!("{} : {} négatif", self.location(node), attribute);
+        }
+        Ok(secs.round() as u32)
+    }
+
+    /// Cadence demandée : `Cadence="90"`, ou `CadenceLow`/`CadenceHigh`
+    fn cadence(&self, node: Node, attribute: &str) -> Result<Option<CadenceTarget>> {
+        if attribute == "Cadence" && node.attribute("CadenceLow").is_some() {
+            return Ok(Some(CadenceTarget::Range {
+                min: self.number(node, "CadenceLow")?.round() as u16,
+                max: self.number(node, "CadenceHigh")?.round() as u16,
+            }));
+        }
+        match node.attribute(attribute) {
+            Some(_) => Ok(Some(CadenceTarget::Rpm(self.number(node, attribute)?.round() as u16))),
+            None => Ok(None),
+        }
+    }
+
+    fn number(&self, node: Node, attribute: &str) -> Result<f64> {
+        let value = node.attribute(attribute)
+            .ok_or_else(|| anyhow!("{} : attribut {} manquant", self.location(node), attribute))?;
+        value.trim().parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| anyhow!("{} : {}=\"{}\" n'est pas un nombre", self.location(node), attribute, value))
+    }
+
+    /// Convertit une fraction de FTP en watts, ramenés dans la plage de l'appareil
+    fn watts(&mut self, node: Node, fraction: f64) -> u16 {
+        let watts = (fraction * self.ftp as f64).round().max(0.0) as u32;
+        let clamped = watts.clamp(self.limits.min as u32, self.limits.max as u32) as u16;
+        if clamped as u32 != watts {
+            self.warn(node, format!("{}W ({:.0}% FTP) ramené à {}W (plage de l'appareil)",
+                                    watts, fraction * 100.0, clamped));
+        }
+        clamped
+    }
+
+    fn warn(&mut self, node: Node, message: String) {
+        let warning = format!("{} : {}", self.location(node), message);
+        println!("⚠️  Import ZWO : {}", warning);
+        self.warnings.push(warning);
+    }
+
+    /// Position d'un élément dans le fichier, pour les messages
+    fn location(&self, node: Node) -> String {
+        let position = self.document.text_pos_at(node.range().start);
+        format!("<{}> ligne {}", node.tag_name().name(), position.row)
+    }
+}
+
+/// Écrit le programme en ZWO, les puissances étant converties en fraction de `ftp`.
+///
+/// Les rampes deviennent `Warmup` (en début de programme), `Cooldown` (en fin) ou `Ramp`,
+/// les blocs répétés de deux paliers `IntervalsT` ; les autres blocs sont déroulés.
+pub fn write(program: &TrainingProgram, ftp: u16) -> Result<String> {
+    let mut workout = String::new();
+    let last = program.intervals.len().saturating_sub(1);
+    for (index, step) in program.intervals.iter().enumerate() {
+        let ramp_tag = match index {
+            0 => "Warmup",
+            i if i == last => "Cooldown",
+            _ => "Ramp",
+        };
+        write_step(&mut workout, step, ftp, ramp_tag)?;
+    }
+
+    let mut out = String::new();
+    writeln!(out, "<workout_file>")?;
+    writeln!(out, "    <author>SkylonRemoteApp</author>")?;
+    writeln!(out, "    <name>{}</name>", escape(&program.name))?;
+    writeln!(out, "    <description>{}</description>", escape(program.description.as_deref().unwrap_or("")))?;
+    writeln!(out, "    <sportType>bike</sportType>")?;
+    writeln!(out, "    <tags/>")?;
+    writeln!(out, "    <workout>")?;
+    out.push_str(&workout);
+    writeln!(out, "    </workout>")?;
+    writeln!(out, "</workout_file>")?;
+    Ok(out)
+}
+
+fn write_step(out: &mut String, step: &ProgramStep, ftp: u16, ramp_tag: &str) -> Result<()> {
+    match step {
+        ProgramStep::Interval(interval) => write_interval(out, interval, ftp, ramp_tag),
+        ProgramStep::Repeat(block) => match as_intervals_t(block) {
+            Some((on, on_power, off, off_power)) => {
+                let mut attributes = format!(
+                    "Repeat=\"{}\" OnDuration=\"{}\" OffDuration=\"{}\" OnPower=\"{}\" OffPower=\"{}\"",
+                    block.repeat, on.duration_secs, off.duration_secs, fraction(on_power, ftp), fraction(off_power, ftp));
+                if let Some(CadenceTarget::Rpm(rpm)) = on.cadence {
+                    write!(attributes, " Cadence=\"{}\"", rpm)?;
+                }
+                if let Some(CadenceTarget::Rpm(rpm)) = off.cadence {
+                    write!(attributes, " CadenceResting=\"{}\"", rpm)?;
+                }
+                write_element(out, "IntervalsT", &attributes, block.name.as_deref())
+            }
+            None => {
+                for _ in 0..block.repeat {
+                    for step in &block.intervals {
+                        write_step(out, step, ftp, "Ramp")?;
+                    }
+                }
+                Ok(())
+            }
+        },
+    }
+}
+
+/// Bloc exprimable en `IntervalsT` : deux paliers de puissance, cadences sans plage
+fn as_intervals_t(block: &RepeatBlock) -> Option<(&TrainingInterval, u16, &TrainingInterval, u16)> {
+    let [ProgramStep::Interval(on), ProgramStep::Interval(off)] = block.intervals.as_slice() else {
+        return None;
+    };
+    let (IntervalTarget::Power { power_target: on_power }, IntervalTarget::Power { power_target: off_power }) =
+        (on.target, off.target) else {
+        return None;
+    };
+    let single = |c: Option<CadenceTarget>| !matches!(c, Some(CadenceTarget::Range { .. }));
+    (single(on.cadence) && single(off.cadence) && on.name.is_none() && off.name.is_none())
+        .then_some((on, on_power, off, off_power))
+}
+
+fn write_interval(out: &mut String, interval: &TrainingInterval, ftp: u16, ramp_tag: &str) -> Result<()> {
+    let (tag, mut attributes) = match interval.target {
+        IntervalTarget::Power { power_target } => ("SteadyState", format!(
+            "Duration=\"{}\" Power=\"{}\"", interval.duration_secs, fraction(power_target, ftp))),
+        IntervalTarget::Ramp { power_start, power_end, .. } => (ramp_tag, format!(
+            "Duration=\"{}\" PowerLow=\"{}\" PowerHigh=\"{}\"",
+            interval.duration_secs, fraction(power_start, ftp), fraction(power_end, ftp))),
+        IntervalTarget::BrakeLevel { .. } => {
+            bail!("Intervalle en niveau de frein : non exportable (consignes en puissance uniquement)")
+        }
+    };
+    match interval.cadence {
+        Some(CadenceTarget::Rpm(rpm)) => write!(attributes, " Cadence=\"{}\"", rpm)?,
+        Some(CadenceTarget::Range { min, max }) => write!(attributes, " CadenceLow=\"{}\" CadenceHigh=\"{}\"", min, max)?,
+        None => {}
+    }
+    write_element(out, tag, &attributes, interval.name.as_deref())
+}
+
+/// Écrit un bloc de `<workout>`, avec son nom en message affiché au début du bloc
+fn write_element(out: &mut String, tag: &str, attributes: &str, name: Option<&str>) -> Result<()> {
+    match name {
+        Some(name) => {
+            writeln!(out, "        <{} {}>", tag, attributes)?;
+            writeln!(out, "            <textevent timeoffset=\"0\" message=\"{}\"/>", escape(name))?;
+            writeln!(out, "        </{}>", tag)?;
+        }
+        None => writeln!(out, "        <{} {}/>", tag, attributes)?,
+    }
+    Ok(())
+}
+
+/// Fraction de FTP, assez précise pour retrouver les watts à l'import
+fn fraction(watts: u16, ftp: u16) -> String {
+    format_decimal(watts as f64 / ftp as f64, 4)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zwo() {
+        let source = r#"<workout_file>
+            <name>Sweet Spot</name>
+            <description>3x8 min</description>
+            <workout>
+                <Warmup Duration="600" PowerLow="0.4" PowerHigh="0.75"/>
+                <SteadyState Duration="300" Power="0.6" Cadence="90">
+                    <textevent timeoffset="0" message="Tempo"/>
+                </SteadyState>
+                <IntervalsT Repeat="3" OnDuration="480" OffDuration="120" OnPower="0.9" OffPower="0.5"
+                            Cadence="95" CadenceResting="85"/>
+                <MaxEffort Duration="20"/>
+                <Freeride Duration="300"/>
+                <Cooldown Duration="300" PowerLow="3" PowerHigh="0.3"/>
+            </workout>
+        </workout_file>"#;
+
+        let imported = parse(source, 200, &PowerLimits::default()).unwrap();
+        let program = &imported.program;
+        assert_eq!(program.id, "sweet-spot");
+        assert_eq!(program.description.as_deref(), Some("3x8 min"));
+        assert_eq!(program.total_duration(), 600 + 300 + 3 * 600 + 300 + 300);
+        assert!(program.validate(&PowerLimits::default(), None).is_empty());
+
+        let ProgramStep::Interval(warmup) = &program.intervals[0] else { panic!() };
+        assert_eq!(warmup.target, IntervalTarget::Ramp { power_start: 80, power_end: 150, step_secs: None });
+
+        let ProgramStep::Interval(steady) = &program.intervals[1] else { panic!() };
+        assert_eq!(steady.target, IntervalTarget::Power { power_target: 120 });
+        assert_eq!(steady.name.as_deref(), Some("Tempo"));
+        assert_eq!(steady.cadence, Some(CadenceTarget::Rpm(90)));
+
+        let ProgramStep::Repeat(block) = &program.intervals[2] else { panic!() };
+        assert_eq!(block.repeat, 3);
+        assert_eq!(block.intervals[0].duration(), 480);
+
+        // MaxEffort ignoré, FreeRide converti, 600W ramenés à 400W
+        assert_eq!(program.intervals.len(), 5);
+        assert_eq!(imported.warnings.len(), 3, "{:?}", imported.warnings);
+        assert!(imported.warnings[0].contains("<MaxEffort> ligne 11"));
+
+        assert!(parse("<workout_file><workout/></workout_file>", 200, &PowerLimits::default()).is_err());
+        assert!(parse("<workout_file><workout><SteadyState Power=\"1\"/></workout></workout_file>",
+                      200, &PowerLimits::default()).is_err());
+    }
+
+    #[test]
+    fn test_write_zwo() {
+        let json = r#"{
+            "id": "sweet-spot-3", "name": "Sweet Spot <3>", "description": "3x8 min & récup",
+            "intervals": [
+                { "duration_secs": 600, "power_start": 80, "power_end": 150 },
+                { "duration_secs": 300, "power_target": 120, "name": "Tempo", "cadence": { "min": 85, "max": 95 } },
+                { "repeat": 3, "intervals": [
+                    { "duration_secs": 480, "power_target": 180, "cadence": 95 },
+                    { "duration_secs": 120, "power_target": 100, "cadence": 85 }
+                ] },
+                { "repeat": 2, "intervals": [
+                    { "duration_secs": 60, "power_target": 250, "name": "Sprint" },
+                    { "duration_secs": 60, "power_target": 100 }
+                ] },
+                { "duration_secs": 300, "power_start": 150, "power_end": 60 }
+            ]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        let written = write(&program, 237).unwrap();
+        assert!(written.contains("<Warmup") && written.contains("<IntervalsT") && written.contains("<Cooldown"));
+        assert!(written.contains("Sweet Spot &lt;3&gt;"));
+
+        // Les rampes, paliers, noms et cadences survivent à la relecture ; le bloc nommé est déroulé
+        let reread = parse(&written, 237, &PowerLimits::default()).unwrap();
+        assert!(reread.warnings.is_empty(), "{:?}", reread.warnings);
+        let reread = reread.program;
+        assert_eq!((reread.name.as_str(), reread.description.as_deref()), ("Sweet Spot <3>", Some("3x8 min & récup")));
+        assert_eq!(reread.intervals.len(), 3 + 4 + 1);
+        assert_eq!(reread.total_duration(), program.total_duration());
+        assert_eq!(serde_json::to_value(&reread.intervals[..3]).unwrap(), serde_json::to_value(&program.intervals[..3]).unwrap());
+        assert_eq!(serde_json::to_value(&reread.intervals[7]).unwrap(), serde_json::to_value(&program.intervals[4]).unwrap());
+    }
+}
@@ -1,12 +1,10 @@
-#[cfg(feature = "real-bluetooth")]
 mod bike_controller;
-
-#[cfg(feature = "mock")]
-mod mock_bike_controller;
-
-#[cfg(feature = "mock")]
-use mock_bike_controller as bike_controller;
-
+mod builtin_programs;
+mod config;
+mod connection;
+mod formats;
+mod storage;
+mod trainer;
 mod training_program;
 
 use actix_web::{get, post, put, delete, web, App, HttpServer, Responder};
@@ -15,13 +13,30 @@ use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 use std::process::Command;
 use bike_controller::BikeController;
-use training_program::{TrainingProgram, TrainingInterval};
+use training_program::{AutoPauseOverride, ProgramExecutionState, ProgramStep, SeekAction, TrainingProgram, ValidationErrors};
+use config::Config;
+use formats::ProgramFormat;
+use trainer::{BackendKind, BrakeMode};
 
 #[derive(Serialize)]
 struct BikeStatus {
     speed: f32,
     rpm: u16,
+    /// Consigne de puissance (conservé pour les interfaces existantes, identique à `power_target`)
     power: u16,
+    power_target: u16,
+    /// Valeurs optionnelles : `null` tant que l'appareil ne les rapporte pas
+    power_actual: Option<u16>,
+    pulse: Option<u16>,
+    /// En centaines de mètres
+    distance: Option<u16>,
+    /// En kJ
+    energy: Option<u16>,
+    /// En secondes
+    time: Option<u16>,
+    device_state: Option<String>,
+    brake_mode: Option<BrakeMode>,
+    brake_level: Option<u8>,
     connected: bool,
 }
 
@@ -31,15 +46,28 @@ async fn status(data: web::Data<Arc<BikeController>>) -> impl Responder {
     web::Json(BikeStatus {
         speed: d.speed,
         rpm: d.rpm,
-        power: d.power,
+        power: d.power_target,
+        power_target: d.power_target,
+        power_actual: d.power_actual,
+        pulse: d.pulse,
+        distance: d.distance,
+        energy: d.energy,
+        time: d.time,
+        device_state: d.device_state,
+        brake_mode: d.brake_mode,
+        brake_level: d.brake_level,
         connected: d.connected,
     })
 }
 
 #[get("/power")]
 async fn get_power(data: web::Data<Arc<BikeController>>) -> impl Responder {
-    let power = data.get_power().await;
-    web::Json(serde_json::json!({"power": power}))
+    let d = data.get_data().await;
+    web::Json(serde_json::json!({
+        "power": d.power_target,
+        "power_target": d.power_target,
+        "power_actual": d.power_actual,
+    }))
 }
 
 #[post("/power/{level}")]
@@ -50,6 +78,117 @@ async fn set_power(level: web::Path<u16>, data: web::Data<Arc<BikeController>>)
     }
 }
 
+// ===== Endpoints pour le mode de freinage =====
+
+/// Mode de freinage actuel (puissance constante ou niveau de frein constant)
+#[get("/mode")]
+async fn get_mode(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    let d = data.get_data().await;
+    web::Json(serde_json::json!({"mode": d.brake_mode}))
+}
+
+/// Change le mode de freinage : `constant_power` ou `constant_brake`
+#[post("/mode/{mode}")]
+async fn set_mode(mode: web::Path<BrakeMode>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.set_brake_mode(*mode).await {
+        Ok(_) => actix_web::HttpResponse::Ok().body("Mode de freinage mis à jour"),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+/// Niveau de frein actuel et bornes rapportées par l'appareil
+#[get("/brake-level")]
+async fn get_brake_level(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    let d = data.get_data().await;
+    let range = data.get_brake_level_range().await;
+    web::Json(serde_json::json!({
+        "brake_level": d.brake_level,
+        "min": range.map(|(min, _)| min),
+        "max": range.map(|(_, max)| max),
+    }))
+}
+
+#[post("/brake-level/{level}")]
+async fn set_brake_level(level: web::Path<u8>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.set_brake_level(*level).await {
+        Ok(_) => actix_web::HttpResponse::Ok().body("Niveau de frein mis à jour"),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+// ===== Endpoints pour les informations de l'appareil =====
+
+/// Limites rapportées par l'appareil et plage de puissance effectivement appliquée
+#[get("/device/limits")]
+async fn get_device_limits(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    let device = data.get_device_limits().await;
+    let power = data.power_limits();
+    web::Json(serde_json::json!({
+        "device": device,
+        "power_min": power.min,
+        "power_max": power.max,
+        "power_source": if data.power_limits_reported() { "device" } else { "fallback" },
+    }))
+}
+
+/// État de la connexion : état, dernière erreur, nombre de tentatives et prochaine tentative
+#[get("/connection")]
+async fn get_connection(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    let connection = data.get_connection().await;
+    let next_retry_in_secs = connection.next_retry_in().map(|d| d.as_secs_f32());
+    let mut json = serde_json::to_value(&connection).unwrap_or_default();
+    json["next_retry_in_secs"] = serde_json::json!(next_retry_in_secs);
+    web::Json(json)
+}
+
+/// Connexion manuelle à l'appareil préféré (réactive la reconnexion automatique)
+#[post("/connection/connect")]
+async fn connect(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.connect().await {
+        Ok(device) => actix_web::HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "device": device,
+        })),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+/// Déconnexion manuelle (suspend la reconnexion automatique)
+#[post("/connection/disconnect")]
+async fn disconnect(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.disconnect().await {
+        Ok(_) => actix_web::HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "message": "Déconnecté"
+        })),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+/// Recherche les appareils à portée (bloque quelques secondes)
+#[get("/devices/scan")]
+async fn scan_devices(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.scan_devices().await {
+        Ok(devices) => actix_web::HttpResponse::Ok().json(serde_json::json!({
+            "devices": devices,
+            "preferred": data.get_preferred_device().await,
+        })),
+        Err(e) => actix_web::HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// Se connecte à l'appareil d'adresse donnée et le retient comme appareil préféré
+#[post("/devices/connect/{addr}")]
+async fn connect_device(addr: web::Path<String>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.connect_device(&addr).await {
+        Ok(device) => actix_web::HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "device": device,
+        })),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
 // ===== Endpoints pour la gestion des programmes d'entraînement =====
 
 #[derive(Deserialize)]
@@ -57,7 +196,22 @@ struct CreateProgramRequest {
     id: String,
     name: String,
     description: Option<String>,
-    intervals: Vec<TrainingInterval>,
+    intervals: Vec<ProgramStep>,
+    #[serde(default)]
+    auto_pause: Option<AutoPauseOverride>,
+}
+
+/// Réponse d'erreur d'une création ou modification de programme : les erreurs de validation
+/// sont détaillées en JSON (`errors`) pour que l'interface signale les champs fautifs
+fn program_error_response(e: anyhow::Error) -> actix_web::HttpResponse {
+    match e.downcast_ref::<ValidationErrors>() {
+        Some(ValidationErrors(errors)) => actix_web::HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "message": e.to_string(),
+            "errors": errors,
+        })),
+        None => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
 }
 
 /// Crée un nouveau programme d'entraînement
@@ -71,6 +225,8 @@ async fn create_program(
         name: req.name.clone(),
         description: req.description.clone(),
         intervals: req.intervals.clone(),
+        builtin: false,
+        auto_pause: req.auto_pause,
     };
 
     match data.create_program(program).await {
@@ -78,15 +234,25 @@ async fn create_program(
             "success": true,
             "message": "Programme créé avec succès"
         })),
-        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+        Err(e) => program_error_response(e),
     }
 }
 
-/// Liste tous les programmes
+#[derive(Deserialize)]
+struct ListProgramsQuery {
+    /// Ajoute la charge prévue (`metrics`) de chaque programme
+    #[serde(default)]
+    metrics: bool,
+}
+
+/// Liste tous les programmes (`?metrics=true` pour trier par difficulté côté interface)
 #[get("/programs")]
-async fn list_programs(data: web::Data<Arc<BikeController>>) -> impl Responder {
-    let programs = data.list_programs().await;
-    web::Json(programs)
+async fn list_programs(query: web::Query<ListProgramsQuery>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    if query.metrics {
+        actix_web::HttpResponse::Ok().json(data.list_programs_with_metrics().await)
+    } else {
+        actix_web::HttpResponse::Ok().json(data.list_programs().await)
+    }
 }
 
 /// Obtient un programme spécifique
@@ -110,6 +276,8 @@ async fn update_program(
         name: req.name.clone(),
         description: req.description.clone(),
         intervals: req.intervals.clone(),
+        builtin: false,
+        auto_pause: req.auto_pause,
     };
 
     match data.update_program(program).await {
@@ -117,10 +285,113 @@ async fn update_program(
             "success": true,
             "message": "Programme mis à jour"
         })),
+        Err(e) => program_error_response(e),
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct CloneProgramRequest {
+    id: Option<String>,
+    name: Option<String>,
+}
+
+/// Copie un programme (notamment un programme intégré) pour pouvoir le modifier
+#[post("/program/{id}/clone")]
+async fn clone_program(
+    id: web::Path<String>,
+    req: Option<web::Json<CloneProgramRequest>>,
+    data: web::Data<Arc<BikeController>>,
+) -> impl Responder {
+    let req = req.map(|r| r.into_inner()).unwrap_or_default();
+
+    match data.clone_program(&id, req.id, req.name).await {
+        Ok(program) => actix_web::HttpResponse::Ok().json(program),
+        Err(e) => program_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct ImportProgramQuery {
+    /// Format du fichier (deviné d'après le contenu si absent)
+    format: Option<String>,
+    /// ID du programme créé (dérivé du nom si absent)
+    id: Option<String>,
+    /// Nom du programme créé (remplace celui du fichier)
+    name: Option<String>,
+}
+
+/// Importe un fichier d'entraînement (corps de la requête) comme nouveau programme
+#[post("/program/import")]
+async fn import_program(
+    query: web::Query<ImportProgramQuery>,
+    body: String,
+    data: web::Data<Arc<BikeController>>,
+) -> impl Responder {
+    let query = query.into_inner();
+    let format = match query.format {
+        Some(format) => format.parse(),
+        None => ProgramFormat::detect(&body)
+            .ok_or_else(|| anyhow::anyhow!("Format de fichier non reconnu : précisez ?format=zwo|erg|mrc|txt")),
+    };
+    let format = match format {
+        Ok(format) => format,
+        Err(e) => return actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    };
+
+    match data.import_program(format, &body, query.id, query.name).await {
+        Ok(imported) => actix_web::HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "program": imported.program,
+            "warnings": imported.warnings,
+        })),
+        Err(e) => program_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportProgramQuery {
+    format: String,
+}
+
+/// Exporte un programme sous forme de fichier (`?format=zwo|erg|mrc|txt`)
+#[get("/program/{id}/export")]
+async fn export_program(
+    id: web::Path<String>,
+    query: web::Query<ExportProgramQuery>,
+    data: web::Data<Arc<BikeController>>,
+) -> impl Responder {
+    let format: ProgramFormat = match query.format.parse() {
+        Ok(format) => format,
+        Err(e) => return actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    };
+
+    match data.export_program(&id, format).await {
+        Ok(content) => actix_web::HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.{}\"", id, format)))
+            .body(content),
         Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
     }
 }
 
+/// Charge prévue d'un programme (kJ, puissances moyenne et normalisée, IF, TSS)
+#[get("/program/{id}/metrics")]
+async fn program_metrics(id: web::Path<String>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.program_metrics(&id).await {
+        Some(metrics) => actix_web::HttpResponse::Ok().json(metrics),
+        None => actix_web::HttpResponse::NotFound().body("Programme introuvable"),
+    }
+}
+
+/// Programme écrit dans la syntaxe texte compacte, pour l'afficher ou le recopier
+#[get("/program/{id}/dsl")]
+async fn program_dsl(id: web::Path<String>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.export_program(&id, ProgramFormat::Txt).await {
+        Ok(content) => actix_web::HttpResponse::Ok().content_type("text/plain; charset=utf-8").body(content),
+        Err(e) => actix_web::HttpResponse::NotFound().body(e.to_string()),
+    }
+}
+
 /// Supprime un programme
 #[delete("/program/{id}")]
 async fn delete_program(id: web::Path<String>, data: web::Data<Arc<BikeController>>) -> impl Responder {
@@ -165,6 +436,84 @@ async fn stop_program(data: web::Data<Arc<BikeController>>) -> impl Responder {
     }
 }
 
+#[derive(Deserialize, Default)]
+struct PauseProgramRequest {
+    recovery_power: Option<u16>,
+}
+
+/// Met le programme en cours en pause
+#[post("/program/pause")]
+async fn pause_program(
+    req: Option<web::Json<PauseProgramRequest>>,
+    data: web::Data<Arc<BikeController>>,
+) -> impl Responder {
+    let req = req.map(|r| r.into_inner()).unwrap_or_default();
+
+    match data.pause_program(req.recovery_power).await {
+        Ok(_) => actix_web::HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "message": "Programme en pause"
+        })),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+/// Reprend le programme en pause
+#[post("/program/resume")]
+async fn resume_program(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    match data.resume_program().await {
+        Ok(_) => actix_web::HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "message": "Programme repris"
+        })),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+/// Passe à l'intervalle suivant du programme en cours
+#[post("/program/next")]
+async fn next_interval(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    state_response(data.seek_program(SeekAction::Next, 0).await)
+}
+
+/// Revient à l'intervalle précédent
+#[post("/program/previous")]
+async fn previous_interval(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    state_response(data.seek_program(SeekAction::Previous, 0).await)
+}
+
+/// Recommence l'intervalle actuel
+#[post("/program/restart-interval")]
+async fn restart_interval(data: web::Data<Arc<BikeController>>) -> impl Responder {
+    state_response(data.seek_program(SeekAction::Restart, 0).await)
+}
+
+/// Se place à une position absolue (en secondes depuis le début du programme)
+#[post("/program/seek/{offset}")]
+async fn seek_program(offset: web::Path<u32>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    state_response(data.seek_program(SeekAction::Offset, offset.into_inner()).await)
+}
+
+/// Décale les consignes du programme en cours (en watts, négatif pour alléger)
+#[post("/program/intensity/offset/{watts}")]
+async fn set_power_offset(watts: web::Path<i16>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    state_response(data.set_program_intensity(Some(watts.into_inner()), None).await)
+}
+
+/// Applique un pourcentage aux consignes du programme en cours (100 = programme tel quel)
+#[post("/program/intensity/percent/{percent}")]
+async fn set_intensity_percent(percent: web::Path<u16>, data: web::Data<Arc<BikeController>>) -> impl Responder {
+    state_response(data.set_program_intensity(None, Some(percent.into_inner())).await)
+}
+
+/// Réponse commune aux commandes qui modifient le programme en cours
+fn state_response(result: anyhow::Result<ProgramExecutionState>) -> actix_web::HttpResponse {
+    match result {
+        Ok(state) => actix_web::HttpResponse::Ok().json(state),
+        Err(e) => actix_web::HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
 /// Obtient l'état du programme actif
 #[get("/program/active")]
 async fn get_active_program(data: web::Data<Arc<BikeController>>) -> impl Responder {
@@ -225,13 +574,20 @@ async fn reboot_system() -> impl Responder {
 async fn main() -> std::io::Result<()> {
     println!("🚀 Démarrage du serveur elliptique...");
 
-    #[cfg(feature = "mock")]
-    println!("🔧 MODE: SIMULATION (mock) - Pas de connexion Bluetooth réelle");
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(2);
+    });
+
+    match config.backend {
+        BackendKind::Mock => println!("🔧 MODE: SIMULATION (mock) - Pas de connexion Bluetooth réelle"),
+        BackendKind::Kettler => println!("🔵 MODE: BLUETOOTH RÉEL - Connexion à un appareil Kettler"),
+    }
 
-    #[cfg(feature = "real-bluetooth")]
-    println!("🔵 MODE: BLUETOOTH RÉEL - Connexion à un appareil Kettler");
+    let trainer = trainer::create(config.backend)
+        .expect("Impossible d'initialiser le backend");
 
-    let bike_controller = BikeController::new().await
+    let bike_controller = BikeController::new(trainer, &config).await
         .expect("Impossible d'initialiser le contrôleur");
 
     bike_controller.clone().start_polling(1); // mise à jour toutes les secondes
@@ -246,15 +602,40 @@ async fn main() -> std::io::Result<()> {
             .service(status)
             .service(get_power)
             .service(set_power)
+            // Endpoints pour le mode de freinage
+            .service(get_mode)
+            .service(set_mode)
+            .service(get_brake_level)
+            .service(set_brake_level)
+            .service(get_device_limits)
+            .service(get_connection)
+            .service(connect)
+            .service(disconnect)
+            .service(scan_devices)
+            .service(connect_device)
             // Endpoints pour les programmes d'entraînement
             .service(create_program)
             .service(list_programs)
+            // Avant `/program/{id}`, qui capturerait "active"
+            .service(get_active_program)
             .service(get_program)
             .service(update_program)
             .service(delete_program)
+            .service(clone_program)
+            .service(import_program)
+            .service(export_program)
+            .service(program_dsl)
+            .service(program_metrics)
             .service(start_program)
             .service(stop_program)
-            .service(get_active_program)
+            .service(pause_program)
+            .service(resume_program)
+            .service(next_interval)
+            .service(previous_interval)
+            .service(restart_interval)
+            .service(seek_program)
+            .service(set_power_offset)
+            .service(set_intensity_percent)
             // Endpoints pour la gestion du système
             .service(shutdown_system)
             .service(reboot_system)
@@ -0,0 +1,84 @@
+//! Persistance sur disque des données du serveur (répertoire `--data-dir`).
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Lit un fichier JSON. Retourne `None` si le fichier n'existe pas.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Lecture de {}", path.display())),
+    };
+
+    let value = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Contenu invalide dans {}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// Écrit un fichier JSON de manière atomique : fichier temporaire, fsync, puis renommage.
+/// Un arrêt brutal laisse soit l'ancienne version, soit la nouvelle, jamais un fichier tronqué.
+pub fn write_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Création du répertoire {}", dir.display()))?;
+
+    let bytes = serde_json::to_vec_pretty(value)?;
+    let tmp_path = path.with_extension("json.tmp");
+
+    {
+        let mut file = fs::File::create(&tmp_path)
+            .with_context(|| format!("Création de {}", tmp_path.display()))?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+    }
+
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Renommage vers {}", path.display()))?;
+
+    // Rendre le renommage durable
+    if let Ok(dir_file) = fs::File::open(dir) {
+        let _ = dir_file.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Liste les fichiers `.json` d'un répertoire (vide si le répertoire n'existe pas)
+pub fn list_json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Lecture du répertoire {}", dir.display())),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        // Les fichiers `.json.tmp` d'une écriture interrompue sont ignorés
+        if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Supprime un fichier de manière durable. Ne fait rien si le fichier n'existe pas.
+pub fn remove_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Suppression de {}", path.display())),
+    }
+
+    if let Ok(dir_file) = fs::File::open(path.parent().unwrap_or(Path::new("."))) {
+        let _ = dir_file.sync_all();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+//! Thread d'entrées/sorties propriétaire du backend matériel.
+//!
+//! Toutes les opérations sur l'appareil passent par un canal de commandes et
+//! sont exécutées dans l'ordre d'arrivée par un unique thread. Les consignes
+//! en attente sont fusionnées (seule la dernière est envoyée) et les mesures
+//! lues périodiquement sont publiées sous forme d'instantanés.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::{Result, anyhow};
+use tokio::sync::{oneshot, watch};
+use super::{BrakeMode, DeviceInfo, DeviceLimits, Telemetry, Trainer};
+
+/// Délai d'attente lorsque le polling n'est pas démarré ou l'appareil non connecté
+const IDLE_WAIT: Duration = Duration::from_millis(500);
+
+/// Mesures publiées par le thread d'E/S après chaque lecture
+#[derive(Debug, Clone)]
+pub struct TelemetrySnapshot {
+    /// Mesures lues, ou l'erreur de lecture
+    pub telemetry: Result<Telemetry, String>,
+    /// Limites de l'appareil, relues tant qu'elles sont incomplètes
+    pub limits: Option<DeviceLimits>,
+}
+
+type Reply<T> = oneshot::Sender<Result<T>>;
+
+enum Command {
+    StartPolling(Duration),
+    Scan(Reply<Vec<DeviceInfo>>),
+    Connect(Option<String>, Reply<(DeviceInfo, DeviceLimits)>),
+    Disconnect(Reply<()>),
+    SetPower(u16, Reply<()>),
+    SetBrakeMode(BrakeMode, Reply<()>),
+    SetBrakeLevel(u8, Reply<()>),
+}
+
+impl Command {
+    /// Consigne remplaçable par une consigne plus récente du même type
+    fn coalesce_key(&self) -> Option<u8> {
+        match self {
+            Command::SetPower(..) => Some(0),
+            Command::SetBrakeLevel(..) => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Répond à une consigne remplacée avant d'avoir été envoyée
+    fn supersede(self) {
+        match self {
+            Command::SetPower(_, reply) | Command::SetBrakeLevel(_, reply) => {
+                let _ = reply.send(Ok(()));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Accès au backend matériel depuis le code async
+pub struct TrainerHandle {
+    name: &'static str,
+    commands: mpsc::Sender<Command>,
+    connected: Arc<AtomicBool>,
+    snapshots: watch::Receiver<Option<TelemetrySnapshot>>,
+}
+
+impl TrainerHandle {
+    /// Démarre le thread d'E/S, qui devient propriétaire du backend
+    pub fn spawn(trainer: Box<dyn Trainer>) -> Result<Self> {
+        let name = trainer.name();
+        let (commands, receiver) = mpsc::channel();
+        let (publisher, snapshots) = watch::channel(None);
+        let connected = Arc::new(AtomicBool::new(trainer.is_connected()));
+
+        let actor = Actor {
+            trainer,
+            commands: receiver,
+            publisher,
+            connected: Arc::clone(&connected),
+            poll_interval: None,
+            limits_complete: false,
+        };
+        thread::Builder::new()
+            .name("trainer-io".to_string())
+            .spawn(move || actor.run())?;
+
+        Ok(Self { name, commands, connected, snapshots })
+    }
+
+    /// Nom court du backend, utilisé dans les logs
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Indique si une connexion est active (état après la dernière commande traitée)
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Acquire)
+    }
+
+    /// Abonnement aux mesures publiées par le thread d'E/S
+    pub fn subscribe(&self) -> watch::Receiver<Option<TelemetrySnapshot>> {
+        self.snapshots.clone()
+    }
+
+    /// Démarre la lecture périodique des mesures
+    pub fn start_polling(&self, interval: Duration) {
+        let _ = self.commands.send(Command::StartPolling(interval));
+    }
+
+    pub async fn scan(&self) -> Result<Vec<DeviceInfo>> {
+        self.request(Command::Scan).await
+    }
+
+    /// Se connecte et retourne l'appareil ainsi que ses limites
+    pub async fn connect(&self, address: Option<String>) -> Result<(DeviceInfo, DeviceLimits)> {
+        self.request(|reply| Command::Connect(address, reply)).await
+    }
+
+    pub async fn disconnect(&self) -> Result<()> {
+        self.request(Command::Disconnect).await
+    }
+
+    pub async fn set_power(&self, watts: u16) -> Result<()> {
+        self.request(|reply| Command::SetPower(watts, reply)).await
+    }
+
+    pub async fn set_brake_mode(&self, mode: BrakeMode) -> Result<()> {
+        self.request(|reply| Command::SetBrakeMode(mode, reply)).await
+    }
+
+    pub async fn set_brake_level(&self, level: u8) -> Result<()> {
+        self.request(|reply| Command::SetBrakeLevel(level, reply)).await
+    }
+
+    async fn request<T>(&self, command: impl FnOnce(Reply<T>) -> Command) -> Result<T> {
+        let (reply, response) = oneshot::channel();
+        self.commands.send(command(reply))
+            .map_err(|_| anyhow!("Thread d'E/S de l'appareil arrêté"))?;
+        response.await
+            .map_err(|_| anyhow!("Thread d'E/S de l'appareil arrêté"))?
+    }
+}
+
+struct Actor {
+    trainer: Box<dyn Trainer>,
+    commands: mpsc::Receiver<Command>,
+    publisher: watch::Sender<Option<TelemetrySnapshot>>,
+    connected: Arc<AtomicBool>,
+    poll_interval: Option<Duration>,
+    // Limites entièrement rapportées pour la connexion courante
+    limits_complete: bool,
+}
+
+impl Actor {
+    fn run(mut self) {
+        let mut next_poll = Instant::now();
+
+        loop {
+            let wait = match self.poll_interval {
+                Some(_) if self.trainer.is_connected() => next_poll.saturating_duration_since(Instant::now()),
+                _ => IDLE_WAIT,
+            };
+
+            match self.commands.recv_timeout(wait) {
+                Ok(first) => {
+                    // Traiter d'un coup toutes les commandes en attente
+                    let mut batch = vec![first];
+                    batch.extend(self.commands.try_iter());
+                    self.execute_batch(batch);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // Tous les `TrainerHandle` ont été libérés
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            // Lecture due, même si des commandes arrivent en continu
+            if let Some(interval) = self.poll_interval
+                && self.trainer.is_connected()
+                && Instant::now() >= next_poll {
+                self.poll();
+                next_poll = Instant::now() + interval;
+            }
+        }
+
+        self.trainer.disconnect();
+    }
+
+    /// Exécute les commandes dans l'ordre, en ne gardant que la dernière consigne de chaque type
+    fn execute_batch(&mut self, batch: Vec<Command>) {
+        let mut last_index = [None; 2];
+        for (i, command) in batch.iter().enumerate() {
+            if let Some(key) = command.coalesce_key() {
+                last_index[key as usize] = Some(i);
+            }
+        }
+
+        for (i, command) in batch.into_iter().enumerate() {
+            match command.coalesce_key() {
+                Some(key) if last_index[key as usize] != Some(i) => command.supersede(),
+                _ => self.execute(command),
+            }
+        }
+
+        self.connected.store(self.trainer.is_connected(), Ordering::Release);
+    }
+
+    fn execute(&mut self, command: Command) {
+        match command {
+            Command::StartPolling(interval) => self.poll_interval = Some(interval),
+            // Bloque le thread d'E/S le temps du scan : refusé pendant un programme (voir `BikeController`)
+            Command::Scan(reply) => {
+                let result = self.trainer.scan();
+                self.respond(reply, result);
+            }
+            Command::Connect(address, reply) => {
+                let result = self.trainer.connect(address.as_deref())
+                    .map(|device| (device, self.trainer.limits()));
+                self.limits_complete = matches!(result, Ok((_, limits)) if limits.is_complete());
+                self.respond(reply, result);
+            }
+            Command::Disconnect(reply) => {
+                self.trainer.disconnect();
+                self.limits_complete = false;
+                self.respond(reply, Ok(()));
+            }
+            Command::SetPower(watts, reply) => {
+                let result = self.trainer.set_power(watts);
+                self.respond(reply, result);
+            }
+            Command::SetBrakeMode(mode, reply) => {
+                let result = self.trainer.set_brake_mode(mode);
+                self.respond(reply, result);
+            }
+            Command::SetBrakeLevel(level, reply) => {
+                let result = self.trainer.set_brake_level(level);
+                self.respond(reply, result);
+            }
+        }
+    }
+
+    /// Publie l'état de connexion avant de répondre : l'appelant lit ainsi `is_connected`
+    /// à jour, y compris après l'échec d'une connexion
+    fn respond<T>(&self, reply: Reply<T>, result: Result<T>) {
+        self.connected.store(self.trainer.is_connected(), Ordering::Release);
+        let _ = reply.send(result);
+    }
+
+    /// Lit les mesures et publie un nouvel instantané
+    fn poll(&mut self) {
+        let telemetry = self.trainer.read_telemetry().map_err(|e| format!("{:#}", e));
+
+        // Certaines limites ne sont rapportées qu'après les premiers échanges
+        let limits = if !self.limits_complete && telemetry.is_ok() {
+            let limits = self.trainer.limits();
+            self.limits_complete = limits.is_complete();
+            Some(limits)
+        } else {
+            None
+        };
+
+        self.connected.store(self.trainer.is_connected(), Ordering::Release);
+        self.publisher.send_replace(Some(TelemetrySnapshot { telemetry, limits }));
+    }
+}
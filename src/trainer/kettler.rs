@@ -0,0 +1,152 @@
+use kdri::{KettlerBrakeMode, KettlerConnection, KettlerDevice, scan_devices};
+use anyhow::Result;
+use super::{BrakeMode, DeviceInfo, DeviceLimits, Telemetry, Trainer};
+
+/// Backend Bluetooth pour les appareils Kettler (via kdri)
+pub struct KettlerTrainer {
+    connection: Option<KettlerConnection>,
+    // Résultats de la dernière recherche, réutilisés par `connect`
+    scanned: Vec<KettlerDevice>,
+}
+
+impl KettlerTrainer {
+    pub fn new() -> Self {
+        Self {
+            connection: None,
+            scanned: Vec::new(),
+        }
+    }
+
+    fn scan_kettler(&mut self) -> Result<()> {
+        self.scanned = scan_devices().map_err(|e| anyhow::anyhow!("Scan failed: {:?}", e))?;
+        Ok(())
+    }
+
+    /// Retire de la dernière recherche l'appareil demandé (ou le dernier trouvé)
+    fn take_scanned(&mut self, address: Option<&str>) -> Option<KettlerDevice> {
+        let index = match address {
+            Some(address) => self.scanned.iter()
+                .position(|d| d.get_addr().to_string().eq_ignore_ascii_case(address))?,
+            None => self.scanned.len().checked_sub(1)?,
+        };
+        Some(self.scanned.remove(index))
+    }
+}
+
+fn device_info(device: &KettlerDevice) -> DeviceInfo {
+    DeviceInfo {
+        name: device.get_name().to_string(),
+        address: device.get_addr().to_string(),
+    }
+}
+
+impl Default for KettlerTrainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Trainer for KettlerTrainer {
+    fn name(&self) -> &'static str {
+        "kettler"
+    }
+
+    fn scan(&mut self) -> Result<Vec<DeviceInfo>> {
+        self.scan_kettler()?;
+        Ok(self.scanned.iter().map(device_info).collect())
+    }
+
+    fn connect(&mut self, address: Option<&str>) -> Result<DeviceInfo> {
+        // Fermer la connexion existante avant d'en ouvrir une nouvelle
+        self.disconnect();
+
+        // Réutiliser la dernière recherche, sinon relancer un scan
+        let device = match self.take_scanned(address) {
+            Some(device) => device,
+            None => {
+                self.scan_kettler()?;
+                self.take_scanned(address).ok_or_else(|| match address {
+                    Some(address) => anyhow::anyhow!("Kettler device {} not found", address),
+                    None => anyhow::anyhow!("No Kettler device found"),
+                })?
+            }
+        };
+
+        let info = device_info(&device);
+        println!("📱 Appareil trouvé : {} ({})", info.name, info.address);
+        println!("🔗 Connexion en cours...");
+        let conn = device.connect().map_err(|e| anyhow::anyhow!("Connect failed: {}", e))?;
+        self.connection = Some(conn);
+        Ok(info)
+    }
+
+    fn disconnect(&mut self) {
+        // La connexion RFCOMM est fermée à la destruction
+        self.connection = None;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    fn read_telemetry(&mut self) -> Result<Telemetry> {
+        let conn = self.connection.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("No active connection"))?;
+
+        Ok(Telemetry {
+            speed: conn.get_speed().map(|v| v as f32 / 10.0).unwrap_or(0.0),
+            rpm: conn.get_rpm().unwrap_or(0),
+            power_target: conn.get_power_target().unwrap_or(0),
+            power_actual: conn.get_power(),
+            pulse: conn.get_pulse(),
+            distance: conn.get_distance(),
+            energy: conn.get_energy(),
+            time: conn.get_time(),
+            device_state: conn.get_device_state().map(|s| format!("{:?}", s).to_lowercase()),
+            brake_mode: conn.get_brake_mode().map(|m| match m {
+                KettlerBrakeMode::ConstantPower => BrakeMode::ConstantPower,
+                KettlerBrakeMode::ConstantBrake => BrakeMode::ConstantBrake,
+            }),
+            brake_level: conn.get_brake_level(),
+            online: conn.get_online(),
+        })
+    }
+
+    fn set_power(&mut self, watts: u16) -> Result<()> {
+        let conn = self.connection.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("No active connection"))?;
+        conn.set_power(watts);
+        Ok(())
+    }
+
+    fn set_brake_mode(&mut self, mode: BrakeMode) -> Result<()> {
+        let conn = self.connection.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("No active connection"))?;
+        conn.set_brake_mode(match mode {
+            BrakeMode::ConstantPower => KettlerBrakeMode::ConstantPower,
+            BrakeMode::ConstantBrake => KettlerBrakeMode::ConstantBrake,
+        });
+        Ok(())
+    }
+
+    fn set_brake_level(&mut self, level: u8) -> Result<()> {
+        let conn = self.connection.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("No active connection"))?;
+        conn.set_brake_level(level);
+        Ok(())
+    }
+
+    fn limits(&mut self) -> DeviceLimits {
+        let Some(conn) = self.connection.as_mut() else {
+            return DeviceLimits::default();
+        };
+
+        DeviceLimits {
+            power_min: conn.get_power_min(),
+            power_max: conn.get_power_max(),
+            power_range: conn.get_power_range(),
+            brake_level_min: conn.get_brake_level_min(),
+            brake_level_max: conn.get_brake_level_max(),
+        }
+    }
+}
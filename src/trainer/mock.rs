@@ -0,0 +1,171 @@
+use std::time::Instant;
+use anyhow::Result;
+use anyhow::bail;
+use super::{BrakeMode, DeviceInfo, DeviceLimits, Telemetry, Trainer};
+
+/// Limits of the simulated device
+const POWER_MIN: u16 = 25;
+const POWER_MAX: u16 = 400;
+const BRAKE_LEVEL_MIN: u8 = 1;
+const BRAKE_LEVEL_MAX: u8 = 20;
+
+/// Address of the simulated device
+const MOCK_ADDRESS: &str = "00:00:00:00:00:00";
+
+/// Backend simulé, sans matériel Bluetooth
+pub struct MockTrainer {
+    started_at: Instant,
+    last_read: Instant,
+    connected: bool,
+    power: u16,
+    brake_mode: BrakeMode,
+    brake_level: u8,
+    // Simulated rider output, lagging behind the target
+    power_actual: f32,
+    // Accumulated since start, like the device counters
+    distance_m: f32,
+    energy_j: f32,
+}
+
+impl MockTrainer {
+    pub fn new() -> Self {
+        println!("🔧 Mode MOCK: Simulation du contrôleur de vélo");
+        println!("   Pas de connexion Bluetooth réelle");
+
+        let now = Instant::now();
+        Self {
+            started_at: now,
+            last_read: now,
+            connected: false,
+            power: 100,
+            brake_mode: BrakeMode::ConstantPower,
+            brake_level: 5,
+            power_actual: 0.0,
+            distance_m: 0.0,
+            energy_j: 0.0,
+        }
+    }
+}
+
+fn mock_device() -> DeviceInfo {
+    DeviceInfo {
+        name: "Kettler MOCK".to_string(),
+        address: MOCK_ADDRESS.to_string(),
+    }
+}
+
+impl Default for MockTrainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Trainer for MockTrainer {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn scan(&mut self) -> Result<Vec<DeviceInfo>> {
+        Ok(vec![mock_device()])
+    }
+
+    fn connect(&mut self, address: Option<&str>) -> Result<DeviceInfo> {
+        // Connection always succeeds in mock mode, but only the simulated device exists
+        self.connected = false;
+        if let Some(address) = address
+            && !address.eq_ignore_ascii_case(MOCK_ADDRESS) {
+            bail!("Mock device {} not found", address);
+        }
+        self.connected = true;
+        Ok(mock_device())
+    }
+
+    fn disconnect(&mut self) {
+        self.connected = false;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Simulates a realistic workout pattern
+    fn read_telemetry(&mut self) -> Result<Telemetry> {
+        if !self.connected {
+            bail!("Mock device disconnected");
+        }
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.started_at).as_secs_f32();
+        let dt = now.duration_since(self.last_read).as_secs_f32();
+        self.last_read = now;
+
+        // Simulate realistic RPM variations (40-80 RPM)
+        let base_rpm = 60;
+        let rpm_variation = ((elapsed * 0.3).sin() * 10.0) as i16;
+        let rpm = (base_rpm + rpm_variation).max(0) as u16;
+
+        // Calculate speed based on RPM (rough approximation)
+        let speed = (rpm as f32) * 0.18; // ~10.8 km/h at 60 RPM
+
+        // In brake mode the output depends on the level and the cadence
+        let demanded = match self.brake_mode {
+            BrakeMode::ConstantPower => self.power as f32,
+            BrakeMode::ConstantBrake => self.brake_level as f32 * rpm as f32 / 4.0,
+        };
+
+        // Actual power converges to the demand (~3 s time constant)
+        // and fluctuates with the pedal stroke
+        let lag = 1.0 - (-dt / 3.0).exp();
+        self.power_actual += (demanded - self.power_actual) * lag;
+        let fluctuation = (elapsed * 1.7).sin() * 0.04 + (elapsed * 0.45).sin() * 0.02;
+        let power_actual = (self.power_actual * (1.0 + fluctuation)).max(0.0);
+
+        // Integrate distance and work over the time since the last read
+        self.distance_m += speed / 3.6 * dt;
+        self.energy_j += power_actual * dt;
+
+        // Heart rate follows the load, with a slow drift
+        let pulse = 80.0 + self.power_actual * 0.35 + (elapsed * 0.05).sin() * 4.0;
+
+        Ok(Telemetry {
+            speed,
+            rpm,
+            // target is set by user or program, so we don't simulate it here
+            power_target: self.power,
+            power_actual: Some(power_actual.round() as u16),
+            pulse: Some(pulse as u16),
+            distance: Some((self.distance_m / 100.0) as u16),
+            energy: Some((self.energy_j / 1000.0) as u16),
+            time: Some(elapsed.min(u16::MAX as f32) as u16),
+            device_state: Some("up".to_string()),
+            brake_mode: Some(self.brake_mode),
+            brake_level: Some(self.brake_level),
+            online: Some(true),
+        })
+    }
+
+    fn set_power(&mut self, watts: u16) -> Result<()> {
+        self.power = watts;
+        Ok(())
+    }
+
+    fn set_brake_mode(&mut self, mode: BrakeMode) -> Result<()> {
+        self.brake_mode = mode;
+        Ok(())
+    }
+
+    fn set_brake_level(&mut self, level: u8) -> Result<()> {
+        self.brake_level = level.clamp(BRAKE_LEVEL_MIN, BRAKE_LEVEL_MAX);
+        Ok(())
+    }
+
+    fn limits(&mut self) -> DeviceLimits {
+        DeviceLimits {
+            power_min: Some(POWER_MIN),
+            power_max: Some(POWER_MAX),
+            power_range: Some(POWER_MAX - POWER_MIN),
+            brake_level_min: Some(BRAKE_LEVEL_MIN),
+            brake_level_max: Some(BRAKE_LEVEL_MAX),
+        }
+    }
+}
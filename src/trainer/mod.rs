@@ -0,0 +1,180 @@
+//! Abstraction des entrées/sorties vers l'appareil d'entraînement.
+//!
+//! Le `BikeController` gère les programmes, leur exécution et la télémétrie ;
+//! seules les opérations propres au matériel (connexion, lecture des mesures,
+//! consigne de puissance) passent par le trait `Trainer`, exécuté sur un
+//! thread dédié (`actor`).
+
+pub mod actor;
+#[cfg(feature = "real-bluetooth")]
+pub mod kettler;
+pub mod mock;
+
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// Mesures lues sur l'appareil lors d'un cycle de polling.
+///
+/// Les valeurs optionnelles valent `None` tant que l'appareil ne les a pas
+/// encore rapportées (ou s'il ne les supporte pas).
+#[derive(Debug, Clone, Default)]
+pub struct Telemetry {
+    /// Vitesse en km/h
+    pub speed: f32,
+    /// Cadence en tours par minute
+    pub rpm: u16,
+    /// Puissance cible (consigne) en watts
+    pub power_target: u16,
+    /// Puissance réellement produite par le cycliste, en watts
+    pub power_actual: Option<u16>,
+    /// Fréquence cardiaque en battements par minute
+    pub pulse: Option<u16>,
+    /// Distance parcourue depuis le début de la séance, en centaines de mètres
+    pub distance: Option<u16>,
+    /// Énergie dépensée depuis le début de la séance, en kJ
+    pub energy: Option<u16>,
+    /// Temps de séance compté par l'appareil, en secondes
+    pub time: Option<u16>,
+    /// État de l'appareil tel que rapporté (ex : "up", "down")
+    pub device_state: Option<String>,
+    /// Mode de freinage actif
+    pub brake_mode: Option<BrakeMode>,
+    /// Niveau de frein courant (mode `ConstantBrake`)
+    pub brake_level: Option<u8>,
+    /// Indicateur "en ligne" rapporté par l'appareil
+    pub online: Option<bool>,
+}
+
+/// Appareil trouvé lors d'une recherche
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// Nom annoncé par l'appareil
+    pub name: String,
+    /// Adresse Bluetooth (ex : "00:11:22:33:44:55")
+    pub address: String,
+}
+
+/// Limites rapportées par l'appareil (`None` si inconnues)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DeviceLimits {
+    /// Puissance minimale en watts
+    pub power_min: Option<u16>,
+    /// Puissance maximale en watts
+    pub power_max: Option<u16>,
+    /// Plage de puissance telle que rapportée par l'appareil
+    pub power_range: Option<u16>,
+    /// Niveau de frein minimal
+    pub brake_level_min: Option<u8>,
+    /// Niveau de frein maximal
+    pub brake_level_max: Option<u8>,
+}
+
+impl DeviceLimits {
+    /// Indique si toutes les limites ont été rapportées
+    pub fn is_complete(&self) -> bool {
+        self.power_min.is_some() && self.power_max.is_some()
+            && self.brake_level_min.is_some() && self.brake_level_max.is_some()
+    }
+}
+
+/// Mode de freinage de l'appareil
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrakeMode {
+    /// Puissance constante (ERG) : la résistance s'adapte à la cadence
+    ConstantPower,
+    /// Niveau de frein constant
+    ConstantBrake,
+}
+
+/// Backend matériel piloté par le `BikeController`.
+///
+/// Les méthodes sont bloquantes : le backend appartient au thread d'E/S
+/// (`actor::TrainerHandle`), jamais appelé directement depuis une tâche async.
+pub trait Trainer: Send {
+    /// Nom court du backend, utilisé dans les logs
+    fn name(&self) -> &'static str;
+
+    /// Recherche les appareils à portée (bloque plusieurs secondes)
+    fn scan(&mut self) -> Result<Vec<DeviceInfo>>;
+
+    /// Se connecte à l'appareil d'adresse `address`, ou au dernier appareil trouvé si `None`.
+    /// Les résultats du dernier `scan` sont réutilisés s'ils contiennent l'appareil.
+    /// Une éventuelle connexion existante est fermée au préalable.
+    fn connect(&mut self, address: Option<&str>) -> Result<DeviceInfo>;
+
+    /// Ferme la connexion courante, s'il y en a une
+    fn disconnect(&mut self);
+
+    /// Indique si une connexion est active
+    fn is_connected(&self) -> bool;
+
+    /// Lit les mesures courantes de l'appareil
+    fn read_telemetry(&mut self) -> Result<Telemetry>;
+
+    /// Envoie une consigne de puissance (en watts) à l'appareil
+    fn set_power(&mut self, watts: u16) -> Result<()>;
+
+    /// Change le mode de freinage (puissance constante ou niveau constant)
+    fn set_brake_mode(&mut self, mode: BrakeMode) -> Result<()>;
+
+    /// Envoie une consigne de niveau de frein (mode `ConstantBrake`)
+    fn set_brake_level(&mut self, level: u8) -> Result<()>;
+
+    /// Limites de puissance et de niveau de frein rapportées par l'appareil
+    fn limits(&mut self) -> DeviceLimits;
+}
+
+/// Backends disponibles, sélectionnés au démarrage (`--backend`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    /// Appareil Kettler réel via Bluetooth
+    Kettler,
+    /// Simulation, sans matériel
+    Mock,
+}
+
+impl Default for BackendKind {
+    /// Kettler si le support Bluetooth est compilé (et que la feature `mock` n'est pas demandée)
+    fn default() -> Self {
+        if cfg!(feature = "real-bluetooth") && !cfg!(feature = "mock") {
+            BackendKind::Kettler
+        } else {
+            BackendKind::Mock
+        }
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "kettler" => Ok(BackendKind::Kettler),
+            "mock" => Ok(BackendKind::Mock),
+            other => bail!("Backend inconnu '{}' (valeurs possibles : kettler, mock)", other),
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendKind::Kettler => write!(f, "kettler"),
+            BackendKind::Mock => write!(f, "mock"),
+        }
+    }
+}
+
+/// Instancie le backend demandé
+pub fn create(kind: BackendKind) -> Result<Box<dyn Trainer>> {
+    match kind {
+        #[cfg(feature = "real-bluetooth")]
+        BackendKind::Kettler => Ok(Box::new(kettler::KettlerTrainer::new())),
+        #[cfg(not(feature = "real-bluetooth"))]
+        BackendKind::Kettler => bail!("Backend 'kettler' indisponible : binaire compilé sans la feature 'real-bluetooth'"),
+        BackendKind::Mock => Ok(Box::new(mock::MockTrainer::new())),
+    }
+}
@@ -1,14 +1,458 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use serde::de::{self, DeserializeOwned, Deserializer};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Consigne d'un intervalle : puissance (mode ERG), rampe de puissance ou niveau de frein.
+///
+/// Sérialisée à plat dans l'intervalle : `"power_target": 150`, `"brake_level": 8`
+/// ou `"power_start": 80, "power_end": 200` (avec `"step_secs"` optionnel).
+/// La désérialisation est écrite à la main pour que les erreurs nomment le champ fautif.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum IntervalTarget {
+    /// Puissance cible en watts (puissance constante)
+    Power { power_target: u16 },
+    /// Niveau de frein (niveau constant)
+    BrakeLevel { brake_level: u8 },
+    /// Puissance variant linéairement de `power_start` à `power_end` sur la durée de l'intervalle,
+    /// mise à jour toutes les `step_secs` secondes (1 par défaut)
+    Ramp {
+        power_start: u16,
+        power_end: u16,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        step_secs: Option<u32>,
+    },
+}
+
+impl<'de> Deserialize<'de> for IntervalTarget {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = Map::deserialize(deserializer)?;
+        if let Some(power_target) = take_field(&mut fields, "power_target")? {
+            return Ok(IntervalTarget::Power { power_target });
+        }
+        if let Some(brake_level) = take_field(&mut fields, "brake_level")? {
+            return Ok(IntervalTarget::BrakeLevel { brake_level });
+        }
+        match (take_field(&mut fields, "power_start")?, take_field(&mut fields, "power_end")?) {
+            (Some(power_start), Some(power_end)) => Ok(IntervalTarget::Ramp {
+                power_start,
+                power_end,
+                step_secs: take_field(&mut fields, "step_secs")?,
+            }),
+            (Some(_), None) => Err(de::Error::missing_field("power_end")),
+            (None, Some(_)) => Err(de::Error::missing_field("power_start")),
+            (None, None) => Err(de::Error::custom(
+                "consigne manquante : `power_target`, `brake_level` ou `power_start`/`power_end` attendu",
+            )),
+        }
+    }
+}
+
+/// Retire et désérialise le champ `name` de `fields`, en nommant le champ en cas d'erreur
+fn take_field<T: DeserializeOwned, E: de::Error>(fields: &mut Map<String, Value>, name: &str) -> Result<Option<T>, E> {
+    fields.remove(name)
+        .map(|value| T::deserialize(value).map_err(|e| E::custom(format!("champ `{}` : {}", name, e))))
+        .transpose()
+}
+
+impl IntervalTarget {
+    /// Puissance à `elapsed` secondes du début d'un intervalle de `duration` secondes
+    /// (`None` pour un niveau de frein). Une rampe commence à `power_start` et sa dernière
+    /// marche est à `power_end`.
+    pub fn power_at(&self, elapsed: u32, duration: u32) -> Option<u16> {
+        match *self {
+            IntervalTarget::Power { power_target } => Some(power_target),
+            IntervalTarget::BrakeLevel { .. } => None,
+            IntervalTarget::Ramp { power_start, power_end, step_secs } => {
+                let step = step_secs.unwrap_or(1).max(1);
+                let last_step = duration.div_ceil(step).saturating_sub(1);
+                let current_step = (elapsed / step).min(last_step);
+                let fraction = if last_step == 0 { 0.0 } else { current_step as f32 / last_step as f32 };
+                let watts = power_start as f32 + (power_end as f32 - power_start as f32) * fraction;
+                Some(watts.round() as u16)
+            }
+        }
+    }
+}
+
+impl fmt::Display for IntervalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalTarget::Power { power_target } => write!(f, "{}W", power_target),
+            IntervalTarget::BrakeLevel { brake_level } => write!(f, "niveau {}", brake_level),
+            IntervalTarget::Ramp { power_start, power_end, .. } => write!(f, "{}W→{}W", power_start, power_end),
+        }
+    }
+}
+
+/// Tolérance (en tr/min, dans les deux sens) autour d'une cadence cible sans plage explicite
+pub const CADENCE_TOLERANCE: u16 = 5;
+
+/// Cadence demandée pendant un intervalle : `"cadence": 90` (± `CADENCE_TOLERANCE`)
+/// ou une plage `"cadence": {"min": 85, "max": 95}` (en tr/min, bornes incluses).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CadenceTarget {
+    Rpm(u16),
+    Range { min: u16, max: u16 },
+}
+
+impl CadenceTarget {
+    /// Bornes de la plage acceptée (en tr/min)
+    pub fn bounds(&self) -> (u16, u16) {
+        match *self {
+            CadenceTarget::Rpm(rpm) => (rpm.saturating_sub(CADENCE_TOLERANCE), rpm.saturating_add(CADENCE_TOLERANCE)),
+            CadenceTarget::Range { min, max } => (min, max),
+        }
+    }
+
+    /// Cadence cible non nulle, plage non vide et non réduite à zéro
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            CadenceTarget::Rpm(rpm) => rpm > 0,
+            CadenceTarget::Range { min, max } => max > 0 && min <= max,
+        }
+    }
+
+    /// Position de la cadence `rpm` par rapport à la plage
+    pub fn status(&self, rpm: u16) -> CadenceStatus {
+        let (min, max) = self.bounds();
+        if rpm < min {
+            CadenceStatus::Below
+        } else if rpm > max {
+            CadenceStatus::Above
+        } else {
+            CadenceStatus::InRange
+        }
+    }
+}
+
+impl fmt::Display for CadenceTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CadenceTarget::Rpm(rpm) => write!(f, "{} tr/min", rpm),
+            CadenceTarget::Range { min, max } => write!(f, "{}-{} tr/min", min, max),
+        }
+    }
+}
+
+/// Cadence mesurée par rapport à la plage demandée
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CadenceStatus {
+    Below,
+    InRange,
+    Above,
+}
+
+/// Respect de la cadence demandée pendant un intervalle du programme déroulé
+#[derive(Debug, Clone, Serialize)]
+pub struct CadenceCompliance {
+    /// Index de l'intervalle dans le programme déroulé
+    pub interval_index: usize,
+    /// Nom de l'intervalle
+    pub interval_name: Option<String>,
+    /// Cadence demandée
+    pub target: CadenceTarget,
+    /// Temps passé dans la plage (en secondes)
+    pub in_range_secs: u32,
+    /// Temps passé au-dessus de la plage (en secondes)
+    pub above_secs: u32,
+    /// Temps passé en dessous de la plage (en secondes)
+    pub below_secs: u32,
+    /// Part du temps passé dans la plage (0-100)
+    pub compliance_percent: f32,
+}
+
+impl CadenceCompliance {
+    fn record(&mut self, status: CadenceStatus, seconds: u32) {
+        match status {
+            CadenceStatus::Below => self.below_secs += seconds,
+            CadenceStatus::InRange => self.in_range_secs += seconds,
+            CadenceStatus::Above => self.above_secs += seconds,
+        }
+        let total = self.in_range_secs + self.above_secs + self.below_secs;
+        if total > 0 {
+            self.compliance_percent = self.in_range_secs as f32 * 100.0 / total as f32;
+        }
+    }
+}
+
+/// Décalage de puissance maximal (en watts, dans les deux sens) réglable en cours de séance
+pub const POWER_OFFSET_MAX: i16 = 200;
+
+/// Intensités (en %) réglables en cours de séance
+pub const INTENSITY_PERCENT_RANGE: RangeInclusive<u16> = 25..=200;
+
+/// Plage de puissance acceptée par l'appareil (en watts, bornes incluses)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PowerLimits {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl Default for PowerLimits {
+    /// Plage historique des appareils Kettler, utilisée tant que l'appareil ne rapporte pas la sienne
+    fn default() -> Self {
+        Self { min: 25, max: 400 }
+    }
+}
+
+impl PowerLimits {
+    pub fn contains(&self, watts: u16) -> bool {
+        (self.min..=self.max).contains(&watts)
+    }
+}
+
+/// Pause automatique d'un programme quand le cycliste arrête de pédaler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoPause {
+    /// Active la pause automatique
+    pub enabled: bool,
+    /// Cadence (tr/min) en dessous de laquelle le cycliste est considéré à l'arrêt
+    pub rpm_threshold: u16,
+    /// Durée (en secondes) sous le seuil avant la mise en pause
+    pub delay_secs: u32,
+}
+
+impl Default for AutoPause {
+    fn default() -> Self {
+        Self { enabled: false, rpm_threshold: 20, delay_secs: 5 }
+    }
+}
+
+/// Pause automatique propre à un programme : les champs absents reprennent le réglage global,
+/// et la pause est activée sauf `"enabled": false`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoPauseOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm_threshold: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_secs: Option<u32>,
+}
+
+impl AutoPauseOverride {
+    /// Réglage effectif, complété par le réglage global `global`
+    pub fn apply(&self, global: AutoPause) -> AutoPause {
+        AutoPause {
+            enabled: self.enabled.unwrap_or(true),
+            rpm_threshold: self.rpm_threshold.unwrap_or(global.rpm_threshold),
+            delay_secs: self.delay_secs.unwrap_or(global.delay_secs),
+        }
+    }
+}
+
+/// Changement d'état provoqué par la pause automatique
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoPauseEvent {
+    Paused,
+    Resumed,
+}
+
+/// Déplacement dans un programme en cours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeekAction {
+    /// Intervalle suivant
+    Next,
+    /// Intervalle précédent
+    Previous,
+    /// Début de l'intervalle actuel
+    Restart,
+    /// Position absolue dans le programme
+    Offset,
+}
+
+/// Dernier déplacement effectué, exposé par `GET /program/active`
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SeekEvent {
+    pub action: SeekAction,
+    /// Position avant le déplacement (en secondes depuis le début du programme)
+    pub from_secs: u32,
+    /// Position après le déplacement
+    pub to_secs: u32,
+    /// Date du déplacement (millisecondes depuis l'epoch Unix)
+    pub at: u64,
+}
+
+/// Raison d'une erreur de validation, sérialisée avec un champ `code`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ValidationReason {
+    /// Valeur ou liste vide
+    Empty,
+    /// ID contenant d'autres caractères que lettres, chiffres, '-' et '_'
+    InvalidCharacters,
+    /// ID déjà utilisé par un autre programme
+    Duplicate,
+    /// Valeur hors de la plage acceptée (`max` absent : pas de borne supérieure)
+    OutOfRange { min: u32, max: Option<u32> },
+    /// Plage dont le minimum dépasse le maximum
+    InvalidRange,
+    /// Programme trop long (nombre d'intervalles déroulés ou durée en secondes)
+    TooLong { max: u64, actual: u64 },
+}
+
+impl fmt::Display for ValidationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationReason::Empty => write!(f, "ne doit pas être vide"),
+            ValidationReason::InvalidCharacters => write!(f, "lettres, chiffres, '-' et '_' uniquement"),
+            ValidationReason::Duplicate => write!(f, "déjà utilisé par un autre programme"),
+            ValidationReason::OutOfRange { min, max: Some(max) } => write!(f, "doit être entre {} et {}", min, max),
+            ValidationReason::OutOfRange { min, max: None } => write!(f, "doit être au moins {}", min),
+            ValidationReason::InvalidRange => write!(f, "plage invalide (minimum supérieur au maximum)"),
+            ValidationReason::TooLong { max, actual } => write!(f, "{} au lieu de {} au maximum", actual, max),
+        }
+    }
+}
+
+/// Erreur de validation d'un programme, localisée pour que l'interface puisse signaler le champ fautif
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    /// Index de l'élément fautif dans `intervals` (absent pour une erreur sur le programme lui-même)
+    pub interval: Option<usize>,
+    /// Indices à l'intérieur des blocs répétés, du plus externe au plus interne
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nested: Vec<usize>,
+    /// Champ fautif (ex : "name", "power_target")
+    pub field: &'static str,
+    pub reason: ValidationReason,
+    /// Description lisible, en français
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: &'static str, reason: ValidationReason) -> Self {
+        let mut error = Self { interval: None, nested: Vec::new(), field, reason, message: String::new() };
+        error.message = error.describe();
+        error
+    }
+
+    /// Localise l'erreur à `path` (indices depuis `intervals`, puis dans les blocs imbriqués)
+    pub fn at(mut self, path: &[usize]) -> Self {
+        self.interval = path.first().copied();
+        self.nested = path.iter().skip(1).copied().collect();
+        self.message = self.describe();
+        self
+    }
+
+    fn describe(&self) -> String {
+        match self.interval {
+            Some(index) => {
+                let position: Vec<String> = std::iter::once(index).chain(self.nested.iter().copied())
+                    .map(|i| (i + 1).to_string())
+                    .collect();
+                format!("Intervalle {}, {} : {}", position.join("."), self.field, self.reason)
+            }
+            None => format!("{} : {}", self.field, self.reason),
+        }
+    }
+}
+
+/// Erreurs de validation d'un programme, transportées dans une `anyhow::Error`
+#[derive(Debug, Clone)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.0.iter().map(|e| e.message.as_str()).collect();
+        write!(f, "Programme invalide : {}", messages.join(" ; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
 
 /// Représente un intervalle dans un programme d'entraînement
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainingInterval {
     /// Durée de l'intervalle en secondes
     pub duration_secs: u32,
-    /// Puissance cible en watts pour cet intervalle
-    pub power_target: u16,
+    /// Consigne de l'intervalle (puissance en watts ou niveau de frein)
+    #[serde(flatten)]
+    pub target: IntervalTarget,
     /// Nom optionnel de l'intervalle (ex: "Échauffement", "Sprint", "Récupération")
     pub name: Option<String>,
+    /// Cadence demandée pendant l'intervalle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cadence: Option<CadenceTarget>,
+}
+
+/// Élément d'un programme : un intervalle, ou un bloc d'éléments répété plusieurs fois.
+///
+/// Un intervalle s'écrit comme avant (`{"duration_secs": 30, "power_target": 300}`),
+/// un bloc avec `{"repeat": 8, "intervals": [...]}` ; les blocs peuvent être imbriqués.
+/// Un objet avec `repeat` est un bloc, sinon un intervalle : l'erreur vient alors du bon type.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ProgramStep {
+    Repeat(RepeatBlock),
+    Interval(TrainingInterval),
+}
+
+impl<'de> Deserialize<'de> for ProgramStep {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let step = if value.get("repeat").is_some() {
+            RepeatBlock::deserialize(value).map(ProgramStep::Repeat)
+        } else {
+            TrainingInterval::deserialize(value).map(ProgramStep::Interval)
+        };
+        step.map_err(de::Error::custom)
+    }
+}
+
+/// Bloc d'éléments répété `repeat` fois
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepeatBlock {
+    /// Nombre de répétitions
+    pub repeat: u32,
+    /// Éléments répétés (intervalles ou blocs imbriqués)
+    pub intervals: Vec<ProgramStep>,
+    /// Nom optionnel du bloc (ex: "Sprints")
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl From<TrainingInterval> for ProgramStep {
+    fn from(interval: TrainingInterval) -> Self {
+        ProgramStep::Interval(interval)
+    }
+}
+
+impl ProgramStep {
+    /// Durée totale de l'élément, répétitions comprises (en secondes)
+    pub fn duration(&self) -> u32 {
+        match self {
+            ProgramStep::Interval(interval) => interval.duration_secs,
+            ProgramStep::Repeat(block) => block.repeat
+                .saturating_mul(block.intervals.iter().map(|s| s.duration()).fold(0, u32::saturating_add)),
+        }
+    }
+}
+
+/// Position dans un bloc répété (« série 3 sur 8 »)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Repetition {
+    /// Nom du bloc
+    pub name: Option<String>,
+    /// Répétition en cours (à partir de 1)
+    pub set: u32,
+    /// Nombre total de répétitions du bloc
+    pub sets: u32,
+}
+
+/// Intervalle d'un programme déroulé, avec sa position dans les blocs répétés
+#[derive(Debug, Clone)]
+pub struct FlatInterval {
+    pub interval: TrainingInterval,
+    /// Blocs englobants, du plus externe au plus interne
+    pub repetitions: Vec<Repetition>,
 }
 
 /// Représente un programme d'entraînement complet
@@ -20,23 +464,239 @@ pub struct TrainingProgram {
     pub name: String,
     /// Description optionnelle
     pub description: Option<String>,
-    /// Liste des intervalles composant le programme
-    pub intervals: Vec<TrainingInterval>,
+    /// Intervalles et blocs répétés composant le programme
+    pub intervals: Vec<ProgramStep>,
+    /// Programme intégré au serveur, en lecture seule
+    #[serde(default)]
+    pub builtin: bool,
+    /// Pause automatique propre au programme (sinon réglage global)
+    #[serde(default)]
+    pub auto_pause: Option<AutoPauseOverride>,
 }
 
 impl TrainingProgram {
     /// Calcule la durée totale du programme en secondes
     pub fn total_duration(&self) -> u32 {
-        self.intervals.iter().map(|i| i.duration_secs).sum()
+        self.intervals.iter().map(|s| s.duration()).fold(0, u32::saturating_add)
     }
 
-    /// Vérifie si le programme est valide
-    pub fn is_valid(&self) -> bool {
-        !self.intervals.is_empty() &&
-        self.intervals.iter().all(|i| i.duration_secs > 0 && i.power_target >= 25 && i.power_target <= 400)
+    /// Liste les erreurs du programme pour la plage de puissance et les bornes du niveau de frein
+    /// données (vide si le programme est valide). Sans bornes connues, seul un niveau nul est refusé.
+    /// L'unicité de l'ID n'est pas vérifiée ici : elle dépend des programmes existants.
+    pub fn validate(&self, limits: &PowerLimits, brake_levels: Option<(u8, u8)>) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        // L'ID sert de nom de fichier
+        if self.id.is_empty() {
+            errors.push(ValidationError::new("id", ValidationReason::Empty));
+        } else if !is_valid_program_id(&self.id) {
+            errors.push(ValidationError::new("id", ValidationReason::InvalidCharacters));
+        }
+        if self.name.trim().is_empty() {
+            errors.push(ValidationError::new("name", ValidationReason::Empty));
+        }
+
+        validate_steps(&self.intervals, limits, brake_levels, &mut Vec::new(), &mut errors);
+
+        let count = self.interval_count();
+        if count > MAX_FLAT_INTERVALS {
+            errors.push(ValidationError::new("intervals",
+                                             ValidationReason::TooLong { max: MAX_FLAT_INTERVALS as u64, actual: count as u64 }));
+        }
+        let duration = self.total_duration();
+        if duration > MAX_PROGRAM_DURATION_SECS {
+            errors.push(ValidationError::new("duration_secs",
+                                             ValidationReason::TooLong { max: MAX_PROGRAM_DURATION_SECS as u64, actual: duration as u64 }));
+        }
+
+        errors
+    }
+
+    /// Nombre d'intervalles une fois les répétitions déroulées
+    pub fn interval_count(&self) -> usize {
+        fn count(steps: &[ProgramStep]) -> usize {
+            steps.iter().map(|step| match step {
+                ProgramStep::Interval(_) => 1,
+                ProgramStep::Repeat(block) => (block.repeat as usize).saturating_mul(count(&block.intervals)),
+            }).fold(0, usize::saturating_add)
+        }
+        count(&self.intervals)
+    }
+
+    /// Déroule les blocs répétés en une liste d'intervalles exécutables
+    pub fn flatten(&self) -> Vec<FlatInterval> {
+        fn walk(steps: &[ProgramStep], repetitions: &mut Vec<Repetition>, out: &mut Vec<FlatInterval>) {
+            for step in steps {
+                match step {
+                    ProgramStep::Interval(interval) => out.push(FlatInterval {
+                        interval: interval.clone(),
+                        repetitions: repetitions.clone(),
+                    }),
+                    ProgramStep::Repeat(block) => {
+                        for set in 1..=block.repeat {
+                            repetitions.push(Repetition { name: block.name.clone(), set, sets: block.repeat });
+                            walk(&block.intervals, repetitions, out);
+                            repetitions.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        if self.interval_count() <= MAX_FLAT_INTERVALS {
+            walk(&self.intervals, &mut Vec::new(), &mut out);
+        }
+        out
+    }
+
+    /// Charge prévue du programme pour une FTP de `ftp` watts. Les intervalles en niveau
+    /// de frein, dont la puissance dépend du pédalage, sont exclus des calculs.
+    pub fn metrics(&self, ftp: u16) -> ProgramMetrics {
+        // Puissance demandée seconde par seconde
+        let mut powers: Vec<f64> = Vec::new();
+        let mut brake_level_secs: u32 = 0;
+        for flat in self.flatten() {
+            let interval = &flat.interval;
+            if let IntervalTarget::BrakeLevel { .. } = interval.target {
+                brake_level_secs += interval.duration_secs;
+                continue;
+            }
+            powers.extend((0..interval.duration_secs)
+                .filter_map(|elapsed| interval.target.power_at(elapsed, interval.duration_secs))
+                .map(f64::from));
+        }
+
+        let power_secs = powers.len() as f64;
+        let work = powers.iter().sum::<f64>();
+        let average = if powers.is_empty() { 0.0 } else { work / power_secs };
+
+        // Puissance normalisée : moyenne glissante sur 30 s, élevée à la puissance 4
+        let window = NORMALIZED_POWER_WINDOW_SECS.min(powers.len()).max(1);
+        let mut rolling = powers.iter().take(window).sum::<f64>();
+        let mut fourth_powers = vec![(rolling / window as f64).powi(4)];
+        for i in window..powers.len() {
+            rolling += powers[i] - powers[i - window];
+            fourth_powers.push((rolling / window as f64).powi(4));
+        }
+        let normalized = (fourth_powers.iter().sum::<f64>() / fourth_powers.len() as f64).powf(0.25);
+
+        let intensity_factor = normalized / ftp.max(1) as f64;
+        let round = |value: f64, decimals: i32| (value * 10f64.powi(decimals)).round() / 10f64.powi(decimals);
+        ProgramMetrics {
+            ftp,
+            duration_secs: self.total_duration(),
+            brake_level_secs,
+            work_kj: round(work / 1000.0, 1),
+            average_power: average.round() as u16,
+            normalized_power: normalized.round() as u16,
+            intensity_factor: round(intensity_factor, 2),
+            tss: round(power_secs * intensity_factor * intensity_factor / 36.0, 1),
+        }
+    }
+}
+
+/// Durée de la moyenne glissante de la puissance normalisée (en secondes)
+const NORMALIZED_POWER_WINDOW_SECS: usize = 30;
+
+/// Charge d'entraînement prévue d'un programme
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgramMetrics {
+    /// FTP de référence (en watts)
+    pub ftp: u16,
+    /// Durée totale du programme (en secondes)
+    pub duration_secs: u32,
+    /// Durée des intervalles en niveau de frein, exclus des calculs (en secondes)
+    pub brake_level_secs: u32,
+    /// Travail mécanique (en kJ)
+    pub work_kj: f64,
+    /// Puissance moyenne (en watts)
+    pub average_power: u16,
+    /// Puissance normalisée (NP, en watts)
+    pub normalized_power: u16,
+    /// Facteur d'intensité (NP / FTP)
+    pub intensity_factor: f64,
+    /// Training Stress Score (100 = une heure à la FTP)
+    pub tss: f64,
+}
+
+/// Programme accompagné de sa charge prévue (liste des programmes avec `?metrics=true`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramWithMetrics {
+    #[serde(flatten)]
+    pub program: TrainingProgram,
+    pub metrics: ProgramMetrics,
+}
+
+/// Vérifie récursivement les éléments `steps`, situés à `path` dans le programme
+/// Vrai si l'ID est utilisable comme nom de fichier (alphanumérique, `-` ou `_`)
+pub fn is_valid_program_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn validate_steps(steps: &[ProgramStep], limits: &PowerLimits, brake_levels: Option<(u8, u8)>,
+                  path: &mut Vec<usize>, errors: &mut Vec<ValidationError>) {
+    if steps.is_empty() {
+        errors.push(ValidationError::new("intervals", ValidationReason::Empty).at(path));
+    }
+
+    let power_range = ValidationReason::OutOfRange { min: limits.min as u32, max: Some(limits.max as u32) };
+    let positive = ValidationReason::OutOfRange { min: 1, max: None };
+    let (brake_min, brake_max) = brake_levels.map_or((1, u8::MAX), |(min, max)| (min.max(1), max));
+    let brake_range = match brake_levels {
+        Some(_) => ValidationReason::OutOfRange { min: brake_min as u32, max: Some(brake_max as u32) },
+        None => positive.clone(),
+    };
+
+    for (index, step) in steps.iter().enumerate() {
+        path.push(index);
+        match step {
+            ProgramStep::Repeat(block) => {
+                if block.repeat == 0 {
+                    errors.push(ValidationError::new("repeat", positive.clone()).at(path));
+                }
+                validate_steps(&block.intervals, limits, brake_levels, path, errors);
+            }
+            ProgramStep::Interval(interval) => {
+                if interval.duration_secs == 0 {
+                    errors.push(ValidationError::new("duration_secs", positive.clone()).at(path));
+                }
+                match interval.target {
+                    IntervalTarget::Power { power_target } if !limits.contains(power_target) => {
+                        errors.push(ValidationError::new("power_target", power_range.clone()).at(path));
+                    }
+                    IntervalTarget::BrakeLevel { brake_level } if !(brake_min..=brake_max).contains(&brake_level) => {
+                        errors.push(ValidationError::new("brake_level", brake_range.clone()).at(path));
+                    }
+                    IntervalTarget::Ramp { power_start, power_end, step_secs } => {
+                        if !limits.contains(power_start) {
+                            errors.push(ValidationError::new("power_start", power_range.clone()).at(path));
+                        }
+                        if !limits.contains(power_end) {
+                            errors.push(ValidationError::new("power_end", power_range.clone()).at(path));
+                        }
+                        if step_secs == Some(0) {
+                            errors.push(ValidationError::new("step_secs", positive.clone()).at(path));
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some(cadence) = interval.cadence
+                    && !cadence.is_valid() {
+                    errors.push(ValidationError::new("cadence", ValidationReason::InvalidRange).at(path));
+                }
+            }
+        }
+        path.pop();
     }
 }
 
+/// Nombre maximal d'intervalles d'un programme déroulé (protège contre les répétitions démesurées)
+pub const MAX_FLAT_INTERVALS: usize = 10_000;
+
+/// Durée maximale d'un programme (24 h)
+pub const MAX_PROGRAM_DURATION_SECS: u32 = 24 * 3600;
+
 /// État de l'exécution d'un programme
 #[derive(Debug, Clone, Serialize)]
 pub struct ProgramExecutionState {
@@ -44,44 +704,170 @@ pub struct ProgramExecutionState {
     pub program_id: String,
     /// Nom du programme
     pub program_name: String,
-    /// Index de l'intervalle actuel (0-based)
+    /// Index de l'intervalle actuel dans le programme déroulé (0-based)
     pub current_interval_index: usize,
+    /// Nombre d'intervalles du programme déroulé (répétitions comprises)
+    pub total_intervals: usize,
+    /// Position dans les blocs répétés englobant l'intervalle actuel, du plus externe au plus interne
+    pub current_repetitions: Vec<Repetition>,
     /// Temps écoulé dans l'intervalle actuel (en secondes)
     pub elapsed_in_interval: u32,
     /// Temps total écoulé depuis le début du programme (en secondes)
     pub total_elapsed: u32,
     /// Durée totale du programme (en secondes)
     pub total_duration: u32,
-    /// Puissance cible actuelle
+    /// Puissance cible actuelle, qui évolue au fil d'une rampe (0 pendant un intervalle en niveau de frein)
     pub current_power_target: u16,
+    /// Niveau de frein cible actuel, si l'intervalle est en niveau de frein
+    pub current_brake_level: Option<u8>,
     /// Nom de l'intervalle actuel
     pub current_interval_name: Option<String>,
+    /// Consigne de l'intervalle actuel telle que définie dans le programme (ex : rampe complète)
+    pub current_interval_target: IntervalTarget,
+    /// Puissance réelle moyenne depuis le début du programme (en watts)
+    pub average_power_actual: f32,
+    /// Travail réalisé depuis le début du programme (en kJ), d'après la puissance réelle
+    pub work_kj: f32,
+    /// Durée couverte par des mesures de puissance réelle (en secondes)
+    #[serde(skip)]
+    measured_secs: u32,
+    /// Cadence demandée pendant l'intervalle actuel
+    pub current_cadence_target: Option<CadenceTarget>,
+    /// Dernière cadence mesurée par rapport à la plage demandée
+    pub cadence_status: Option<CadenceStatus>,
+    /// Respect de la cadence, pour chaque intervalle parcouru ayant une cadence demandée
+    pub cadence_compliance: Vec<CadenceCompliance>,
+    /// Programme en pause : le temps d'exécution n'avance plus
+    pub paused: bool,
+    /// Début de la pause en cours (millisecondes depuis l'epoch Unix)
+    pub paused_at: Option<u64>,
+    /// Durée cumulée des pauses (en secondes)
+    pub paused_secs: u32,
+    /// Puissance de récupération appliquée pendant la pause en cours
+    pub recovery_power: Option<u16>,
+    /// La pause en cours a été déclenchée par l'arrêt du pédalage
+    pub auto_paused: bool,
+    /// Durée consécutive sous le seuil de cadence (en secondes)
+    #[serde(skip)]
+    low_rpm_secs: u32,
+    /// Dernier déplacement manuel dans le programme
+    pub last_seek: Option<SeekEvent>,
+    /// Décalage ajouté aux consignes de puissance (en watts)
+    pub power_offset: i16,
+    /// Intensité appliquée aux consignes de puissance (en %, 100 = programme tel quel)
+    pub intensity_percent: u16,
+    /// Consigne de puissance réellement envoyée (intensité et décalage appliqués, bornée
+    /// à la plage de l'appareil ; 0 pendant un intervalle en niveau de frein)
+    pub effective_power_target: u16,
     /// Programme complet pour référence
     pub program: TrainingProgram,
+    /// Programme déroulé, parcouru par l'exécution
+    #[serde(skip)]
+    flat: Vec<FlatInterval>,
 }
 
 impl ProgramExecutionState {
     pub fn new(program: TrainingProgram) -> Self {
         let total_duration = program.total_duration();
-        let current_power_target = program.intervals.first()
-            .map(|i| i.power_target)
-            .unwrap_or(0);
-        let current_interval_name = program.intervals.first()
-            .and_then(|i| i.name.clone());
+        let flat = program.flatten();
 
-        Self {
+        let mut state = Self {
             program_id: program.id.clone(),
             program_name: program.name.clone(),
             current_interval_index: 0,
+            total_intervals: flat.len(),
+            current_repetitions: Vec::new(),
             elapsed_in_interval: 0,
             total_elapsed: 0,
             total_duration,
-            current_power_target,
-            current_interval_name,
+            current_power_target: 0,
+            current_brake_level: None,
+            current_interval_name: None,
+            current_interval_target: IntervalTarget::Power { power_target: 0 },
+            average_power_actual: 0.0,
+            work_kj: 0.0,
+            measured_secs: 0,
+            current_cadence_target: None,
+            cadence_status: None,
+            cadence_compliance: Vec::new(),
+            paused: false,
+            paused_at: None,
+            paused_secs: 0,
+            recovery_power: None,
+            auto_paused: false,
+            low_rpm_secs: 0,
+            last_seek: None,
+            power_offset: 0,
+            intensity_percent: 100,
+            effective_power_target: 0,
             program,
+            flat,
+        };
+        state.load_interval(0);
+        state.effective_power_target = state.current_power_target;
+        state
+    }
+
+    /// Charge la consigne et le nom de l'intervalle `index`.
+    /// Retourne false si l'intervalle n'existe pas.
+    fn load_interval(&mut self, index: usize) -> bool {
+        let Some(FlatInterval { interval, repetitions }) = self.flat.get(index) else {
+            return false;
+        };
+
+        match interval.target {
+            IntervalTarget::BrakeLevel { brake_level } => {
+                self.current_power_target = 0;
+                self.current_brake_level = Some(brake_level);
+            }
+            target => {
+                self.current_power_target = target
+                    .power_at(self.elapsed_in_interval, interval.duration_secs)
+                    .unwrap_or(0);
+                self.current_brake_level = None;
+            }
+        }
+        self.current_interval_target = interval.target;
+        self.current_interval_name = interval.name.clone();
+        self.current_cadence_target = interval.cadence;
+        self.cadence_status = None;
+        self.current_repetitions = repetitions.clone();
+        true
+    }
+
+    /// Consigne de l'intervalle actuel
+    pub fn current_target(&self) -> IntervalTarget {
+        match self.current_brake_level {
+            Some(brake_level) => IntervalTarget::BrakeLevel { brake_level },
+            None => IntervalTarget::Power { power_target: self.current_power_target },
         }
     }
 
+    /// Consigne à envoyer à l'appareil : celle de l'intervalle, mise à l'échelle par
+    /// `intensity_percent`, décalée de `power_offset` puis bornée à `limits`.
+    /// Les niveaux de frein ne sont pas modifiés.
+    pub fn effective_target(&self, limits: &PowerLimits) -> IntervalTarget {
+        match self.current_target() {
+            IntervalTarget::Power { power_target } => {
+                let scaled = (power_target as f32 * self.intensity_percent as f32 / 100.0).round() as i32;
+                let watts = (scaled + self.power_offset as i32)
+                    .clamp(limits.min as i32, limits.max as i32) as u16;
+                IntervalTarget::Power { power_target: watts }
+            }
+            target => target,
+        }
+    }
+
+    /// Calcule la consigne effective, la reporte dans `effective_power_target` et la retourne
+    pub fn update_effective_target(&mut self, limits: &PowerLimits) -> IntervalTarget {
+        let target = self.effective_target(limits);
+        self.effective_power_target = match target {
+            IntervalTarget::Power { power_target } => power_target,
+            _ => 0,
+        };
+        target
+    }
+
     /// Avance le temps d'exécution et change d'intervalle si nécessaire
     /// Retourne true si le programme est terminé
     pub fn advance(&mut self, seconds: u32) -> bool {
@@ -89,26 +875,162 @@ impl ProgramExecutionState {
         self.total_elapsed += seconds;
 
         // Vérifier si on doit passer à l'intervalle suivant
-        if let Some(current_interval) = self.program.intervals.get(self.current_interval_index) {
-            if self.elapsed_in_interval >= current_interval.duration_secs {
-                // Passer à l'intervalle suivant
-                self.current_interval_index += 1;
-                self.elapsed_in_interval = 0;
-
-                // Mettre à jour la puissance cible et le nom
-                if let Some(next_interval) = self.program.intervals.get(self.current_interval_index) {
-                    self.current_power_target = next_interval.power_target;
-                    self.current_interval_name = next_interval.name.clone();
-                } else {
-                    // Programme terminé
-                    return true;
-                }
+        if let Some(current) = self.flat.get(self.current_interval_index)
+            && self.elapsed_in_interval >= current.interval.duration_secs {
+            // Passer à l'intervalle suivant
+            self.current_interval_index += 1;
+            self.elapsed_in_interval = 0;
+
+            // Mettre à jour la consigne et le nom
+            if !self.load_interval(self.current_interval_index) {
+                // Programme terminé
+                return true;
             }
+        } else if let Some(FlatInterval { interval, .. }) = self.flat.get(self.current_interval_index)
+            && let IntervalTarget::Ramp { .. } = interval.target {
+            // Progression le long de la rampe
+            self.current_power_target = interval.target
+                .power_at(self.elapsed_in_interval, interval.duration_secs)
+                .unwrap_or(0);
         }
 
         false
     }
 
+    /// Met le programme en pause à l'instant `now_ms` (millisecondes depuis l'epoch Unix).
+    /// Retourne false s'il était déjà en pause.
+    pub fn pause(&mut self, now_ms: u64, recovery_power: Option<u16>) -> bool {
+        if self.paused {
+            return false;
+        }
+        self.paused = true;
+        self.paused_at = Some(now_ms);
+        self.recovery_power = recovery_power;
+        self.auto_paused = false;
+        true
+    }
+
+    /// Reprend l'exécution. Retourne false si le programme n'était pas en pause.
+    pub fn resume(&mut self) -> bool {
+        if !self.paused {
+            return false;
+        }
+        self.paused = false;
+        self.paused_at = None;
+        self.recovery_power = None;
+        self.auto_paused = false;
+        true
+    }
+
+    /// Met en pause après `delay_secs` secondes sous le seuil de cadence, et reprend
+    /// dès que le pédalage redémarre (seulement si la pause était automatique).
+    /// À appeler toutes les `seconds` secondes avec la cadence mesurée.
+    pub fn update_auto_pause(&mut self, rpm: u16, seconds: u32, settings: &AutoPause,
+                             now_ms: u64, recovery_power: Option<u16>) -> Option<AutoPauseEvent> {
+        if !settings.enabled {
+            self.low_rpm_secs = 0;
+            return None;
+        }
+
+        if rpm >= settings.rpm_threshold {
+            self.low_rpm_secs = 0;
+            if self.auto_paused {
+                self.resume();
+                return Some(AutoPauseEvent::Resumed);
+            }
+            return None;
+        }
+
+        if self.paused {
+            return None;
+        }
+
+        self.low_rpm_secs += seconds;
+        if self.low_rpm_secs >= settings.delay_secs {
+            self.pause(now_ms, recovery_power);
+            self.auto_paused = true;
+            return Some(AutoPauseEvent::Paused);
+        }
+        None
+    }
+
+    /// Comptabilise `seconds` secondes passées en pause
+    pub fn tick_paused(&mut self, seconds: u32) {
+        self.paused_secs += seconds;
+    }
+
+    /// Position de début de l'intervalle `index` (en secondes depuis le début du programme)
+    pub fn interval_start(&self, index: usize) -> Option<u32> {
+        if index >= self.flat.len() {
+            return None;
+        }
+        Some(self.flat[..index].iter().map(|f| f.interval.duration_secs).sum())
+    }
+
+    /// Se place à `offset_secs` secondes du début du programme et charge la consigne correspondante.
+    /// Retourne false si la position est au-delà de la fin du programme.
+    pub fn seek(&mut self, action: SeekAction, offset_secs: u32, now_ms: u64) -> bool {
+        let mut start = 0;
+        for (index, FlatInterval { interval, .. }) in self.flat.iter().enumerate() {
+            if offset_secs < start + interval.duration_secs {
+                self.last_seek = Some(SeekEvent {
+                    action,
+                    from_secs: self.total_elapsed,
+                    to_secs: offset_secs,
+                    at: now_ms,
+                });
+                self.current_interval_index = index;
+                self.elapsed_in_interval = offset_secs - start;
+                self.total_elapsed = offset_secs;
+                return self.load_interval(index);
+            }
+            start += interval.duration_secs;
+        }
+        false
+    }
+
+    /// Enregistre la puissance réellement produite pendant `seconds` secondes
+    pub fn record_power_actual(&mut self, watts: u16, seconds: u32) {
+        let total = self.measured_secs + seconds;
+        if total == 0 {
+            return;
+        }
+        self.average_power_actual = (self.average_power_actual * self.measured_secs as f32
+            + watts as f32 * seconds as f32) / total as f32;
+        self.work_kj += watts as f32 * seconds as f32 / 1000.0;
+        self.measured_secs = total;
+    }
+
+    /// Enregistre la cadence mesurée pendant `seconds` secondes dans l'intervalle actuel
+    pub fn record_cadence(&mut self, rpm: u16, seconds: u32) {
+        let Some(target) = self.current_cadence_target else {
+            return;
+        };
+        let status = target.status(rpm);
+        self.cadence_status = Some(status);
+
+        let index = self.current_interval_index;
+        if !self.cadence_compliance.iter().any(|c| c.interval_index == index) {
+            self.cadence_compliance.push(CadenceCompliance {
+                interval_index: index,
+                interval_name: self.current_interval_name.clone(),
+                target,
+                in_range_secs: 0,
+                above_secs: 0,
+                below_secs: 0,
+                compliance_percent: 0.0,
+            });
+        }
+        if let Some(compliance) = self.cadence_compliance.iter_mut().find(|c| c.interval_index == index) {
+            compliance.record(status, seconds);
+        }
+    }
+
+    /// Respect de la cadence dans l'intervalle `index`, s'il en demandait une
+    pub fn cadence_compliance_for(&self, index: usize) -> Option<&CadenceCompliance> {
+        self.cadence_compliance.iter().find(|c| c.interval_index == index)
+    }
+
     /// Calcule le pourcentage de progression (0-100)
     pub fn progress_percentage(&self) -> f32 {
         if self.total_duration == 0 {
@@ -127,6 +1049,23 @@ impl ProgramExecutionState {
 mod tests {
     use super::*;
 
+    /// Programme de test composé des éléments `intervals`
+    fn program_with(intervals: Vec<ProgramStep>) -> TrainingProgram {
+        TrainingProgram {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            intervals,
+            builtin: false,
+            auto_pause: None,
+        }
+    }
+
+    /// Intervalle sans nom ni cadence
+    fn interval(duration_secs: u32, target: IntervalTarget) -> ProgramStep {
+        ProgramStep::Interval(TrainingInterval { duration_secs, target, name: None, cadence: None })
+    }
+
     #[test]
     fn test_program_total_duration() {
         let program = TrainingProgram {
@@ -134,22 +1073,34 @@ mod tests {
             name: "Test Program".to_string(),
             description: None,
             intervals: vec![
-                TrainingInterval {
+                ProgramStep::Interval(TrainingInterval {
                     duration_secs: 60,
-                    power_target: 100,
+                    target: IntervalTarget::Power { power_target: 100 },
                     name: Some("Warmup".to_string()),
-                },
-                TrainingInterval {
+                    cadence: None,
+                }),
+                ProgramStep::Interval(TrainingInterval {
                     duration_secs: 120,
-                    power_target: 200,
+                    target: IntervalTarget::Power { power_target: 200 },
                     name: Some("Work".to_string()),
-                },
+                    cadence: None,
+                }),
             ],
+            builtin: false,
+            auto_pause: None,
         };
 
         assert_eq!(program.total_duration(), 180);
     }
 
+    #[test]
+    fn test_program_validity_uses_limits() {
+        let program = program_with(vec![interval(60, IntervalTarget::Power { power_target: 450 })]);
+
+        assert!(!program.validate(&PowerLimits::default(), None).is_empty());
+        assert!(program.validate(&PowerLimits { min: 10, max: 600 }, None).is_empty());
+    }
+
     #[test]
     fn test_execution_state_advance() {
         let program = TrainingProgram {
@@ -157,17 +1108,21 @@ mod tests {
             name: "Test".to_string(),
             description: None,
             intervals: vec![
-                TrainingInterval {
+                ProgramStep::Interval(TrainingInterval {
                     duration_secs: 10,
-                    power_target: 100,
+                    target: IntervalTarget::Power { power_target: 100 },
                     name: None,
-                },
-                TrainingInterval {
+                    cadence: None,
+                }),
+                ProgramStep::Interval(TrainingInterval {
                     duration_secs: 10,
-                    power_target: 200,
+                    target: IntervalTarget::Power { power_target: 200 },
                     name: None,
-                },
+                    cadence: None,
+                }),
             ],
+            builtin: false,
+            auto_pause: None,
         };
 
         let mut state = ProgramExecutionState::new(program);
@@ -191,4 +1146,313 @@ mod tests {
         let finished = state.advance(10);
         assert!(finished);
     }
+
+    #[test]
+    fn test_record_power_actual() {
+        let program = program_with(vec![interval(60, IntervalTarget::Power { power_target: 150 })]);
+
+        let mut state = ProgramExecutionState::new(program);
+        state.record_power_actual(100, 10);
+        state.record_power_actual(200, 10);
+
+        assert_eq!(state.average_power_actual, 150.0);
+        assert_eq!(state.work_kj, 3.0);
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let program = program_with(vec![interval(60, IntervalTarget::Power { power_target: 150 })]);
+
+        let mut state = ProgramExecutionState::new(program);
+        state.advance(10);
+
+        assert!(state.pause(1_000, Some(50)));
+        assert!(!state.pause(2_000, None));
+        assert_eq!(state.paused_at, Some(1_000));
+        assert_eq!(state.recovery_power, Some(50));
+        state.tick_paused(5);
+
+        assert!(state.resume());
+        assert!(!state.resume());
+        assert_eq!(state.paused_secs, 5);
+        assert_eq!(state.total_elapsed, 10);
+        assert_eq!(state.current_target(), IntervalTarget::Power { power_target: 150 });
+    }
+
+    #[test]
+    fn test_auto_pause_and_resume() {
+        let program = program_with(vec![interval(600, IntervalTarget::Power { power_target: 150 })]);
+        let settings = AutoPause { enabled: true, rpm_threshold: 20, delay_secs: 3 };
+
+        let mut state = ProgramExecutionState::new(program);
+        assert_eq!(state.update_auto_pause(5, 1, &settings, 0, None), None);
+        assert_eq!(state.update_auto_pause(5, 1, &settings, 0, None), None);
+        assert_eq!(state.update_auto_pause(5, 1, &settings, 0, None), Some(AutoPauseEvent::Paused));
+        assert!(state.paused && state.auto_paused);
+
+        assert_eq!(state.update_auto_pause(60, 1, &settings, 0, None), Some(AutoPauseEvent::Resumed));
+        assert!(!state.paused);
+
+        // Une pause manuelle n'est pas levée par la reprise du pédalage
+        state.pause(0, None);
+        assert_eq!(state.update_auto_pause(60, 1, &settings, 0, None), None);
+        assert!(state.paused);
+
+        // Un réglage partiel du programme active la pause et complète le réglage global
+        let global = AutoPause { enabled: false, rpm_threshold: 25, delay_secs: 8 };
+        let partial: AutoPauseOverride = serde_json::from_str(r#"{"rpm_threshold": 30}"#).unwrap();
+        assert_eq!(partial.apply(global), AutoPause { enabled: true, rpm_threshold: 30, delay_secs: 8 });
+        let disabled: AutoPauseOverride = serde_json::from_str(r#"{"enabled": false}"#).unwrap();
+        assert!(!disabled.apply(AutoPause { enabled: true, ..global }).enabled);
+    }
+
+    #[test]
+    fn test_seek() {
+        let program = program_with(vec![
+            interval(60, IntervalTarget::Power { power_target: 100 }),
+            interval(120, IntervalTarget::BrakeLevel { brake_level: 8 }),
+        ]);
+
+        let mut state = ProgramExecutionState::new(program);
+        assert_eq!(state.interval_start(1), Some(60));
+        assert_eq!(state.interval_start(2), None);
+
+        assert!(state.seek(SeekAction::Offset, 90, 0));
+        assert_eq!(state.current_interval_index, 1);
+        assert_eq!(state.elapsed_in_interval, 30);
+        assert_eq!(state.total_elapsed, 90);
+        assert_eq!(state.current_target(), IntervalTarget::BrakeLevel { brake_level: 8 });
+
+        assert!(state.seek(SeekAction::Previous, 0, 0));
+        assert_eq!(state.current_target(), IntervalTarget::Power { power_target: 100 });
+        assert_eq!(state.last_seek.map(|e| e.from_secs), Some(90));
+
+        assert!(!state.seek(SeekAction::Offset, 180, 0));
+        assert_eq!(state.total_elapsed, 0);
+    }
+
+    #[test]
+    fn test_step_errors_name_the_field() {
+        let error = |json: &str| serde_json::from_str::<ProgramStep>(json).unwrap_err().to_string();
+        assert!(error(r#"{"duration_secs": 30, "power_target": "fort"}"#).contains("champ `power_target`"));
+        assert!(error(r#"{"duration_secs": 30, "power_start": 100}"#).contains("`power_end`"));
+        assert!(error(r#"{"duration_secs": 30}"#).contains("consigne manquante"));
+        assert!(error(r#"{"repeat": 2, "intervals": [{"power_target": 100}]}"#).contains("`duration_secs`"));
+    }
+
+    #[test]
+    fn test_effective_target_scales_offsets_and_clamps() {
+        let program = program_with(vec![interval(60, IntervalTarget::Power { power_target: 200 })]);
+        let limits = PowerLimits { min: 25, max: 400 };
+
+        let mut state = ProgramExecutionState::new(program);
+        state.intensity_percent = 110;
+        state.power_offset = -10;
+        assert_eq!(state.effective_target(&limits), IntervalTarget::Power { power_target: 210 });
+        assert_eq!(state.effective_power_target, 200);
+        assert_eq!(state.update_effective_target(&limits), IntervalTarget::Power { power_target: 210 });
+        assert_eq!(state.effective_power_target, 210);
+
+        state.intensity_percent = 200;
+        state.power_offset = 100;
+        assert_eq!(state.effective_target(&limits), IntervalTarget::Power { power_target: 400 });
+
+        state.intensity_percent = 25;
+        state.power_offset = -200;
+        assert_eq!(state.effective_target(&limits), IntervalTarget::Power { power_target: 25 });
+    }
+
+    #[test]
+    fn test_ramp_interval() {
+        let program = program_with(vec![interval(100, IntervalTarget::Ramp { power_start: 100, power_end: 200, step_secs: Some(10) })]);
+        assert!(program.validate(&PowerLimits::default(), None).is_empty());
+
+        // 10 marches de 10 s, de 100W à 200W
+        let mut state = ProgramExecutionState::new(program);
+        assert_eq!(state.current_power_target, 100);
+        state.advance(9);
+        assert_eq!(state.current_power_target, 100);
+        state.advance(1);
+        assert_eq!(state.current_power_target, 111);
+        state.advance(45);
+        assert_eq!(state.current_power_target, 156);
+
+        assert!(state.seek(SeekAction::Offset, 90, 0));
+        assert_eq!(state.current_power_target, 200);
+
+        // La dernière seconde de la rampe atteint `power_end`, avec ou sans marches
+        for (duration, step_secs) in [(100, Some(10)), (95, Some(10)), (60, None)] {
+            let target = IntervalTarget::Ramp { power_start: 100, power_end: 200, step_secs };
+            let flat = program_with(vec![interval(duration, target)]).flatten();
+            let last = &flat.last().unwrap().interval;
+            assert_eq!(last.target.power_at(last.duration_secs - 1, last.duration_secs), Some(200));
+            assert_eq!(last.target.power_at(0, last.duration_secs), Some(100));
+        }
+
+        let json = serde_json::to_value(&state.program.intervals[0]).unwrap();
+        assert_eq!(json["power_start"], 100);
+        assert_eq!(json["step_secs"], 10);
+    }
+
+    #[test]
+    fn test_repeat_blocks() {
+        let json = r#"{
+            "id": "sprints", "name": "Sprints", "description": null,
+            "intervals": [
+                { "duration_secs": 300, "power_target": 100 },
+                { "repeat": 8, "name": "Sprints", "intervals": [
+                    { "duration_secs": 30, "power_target": 300 },
+                    { "repeat": 2, "intervals": [{ "duration_secs": 15, "power_target": 100 }] }
+                ]}
+            ]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        assert_eq!(program.total_duration(), 300 + 8 * 60);
+        assert_eq!(program.interval_count(), 1 + 8 * 3);
+        assert!(program.validate(&PowerLimits::default(), None).is_empty());
+
+        let mut state = ProgramExecutionState::new(program);
+        assert!(state.current_repetitions.is_empty());
+        assert!(state.seek(SeekAction::Offset, 300 + 2 * 60 + 45, 0));
+        assert_eq!(state.current_repetitions, vec![
+            Repetition { name: Some("Sprints".to_string()), set: 3, sets: 8 },
+            Repetition { name: None, set: 2, sets: 2 },
+        ]);
+        assert_eq!(state.current_target(), IntervalTarget::Power { power_target: 100 });
+    }
+
+    #[test]
+    fn test_cadence_compliance() {
+        let json = r#"{
+            "id": "cadence", "name": "Cadence", "description": null,
+            "intervals": [
+                { "duration_secs": 10, "power_target": 100, "cadence": 90 },
+                { "duration_secs": 10, "power_target": 100 },
+                { "duration_secs": 10, "power_target": 100, "cadence": { "min": 60, "max": 70 } }
+            ]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        assert!(program.validate(&PowerLimits::default(), None).is_empty());
+
+        let mut state = ProgramExecutionState::new(program);
+        assert_eq!(state.current_cadence_target, Some(CadenceTarget::Rpm(90)));
+
+        // 6s dans la plage (85-95), 2s au-dessus, 2s en dessous
+        for rpm in [90, 92, 85, 95, 88, 91, 100, 97, 70, 0] {
+            state.record_cadence(rpm, 1);
+            state.advance(1);
+        }
+        let first = state.cadence_compliance_for(0).unwrap();
+        assert_eq!((first.in_range_secs, first.above_secs, first.below_secs), (6, 2, 2));
+        assert_eq!(first.compliance_percent, 60.0);
+
+        // Pas de cadence demandée : rien n'est enregistré
+        state.record_cadence(30, 5);
+        assert!(state.cadence_status.is_none());
+        assert!(state.cadence_compliance_for(1).is_none());
+
+        state.advance(10);
+        state.record_cadence(75, 1);
+        assert_eq!(state.cadence_status, Some(CadenceStatus::Above));
+        assert_eq!(state.cadence_compliance.len(), 2);
+
+        let mut invalid = state.program.clone();
+        if let ProgramStep::Interval(interval) = &mut invalid.intervals[0] {
+            interval.cadence = Some(CadenceTarget::Range { min: 95, max: 85 });
+        }
+        assert!(!invalid.validate(&PowerLimits::default(), None).is_empty());
+        if let ProgramStep::Interval(interval) = &mut invalid.intervals[0] {
+            interval.cadence = Some(CadenceTarget::Rpm(0));
+        }
+        assert!(!invalid.validate(&PowerLimits::default(), None).is_empty());
+    }
+
+    #[test]
+    fn test_program_metrics() {
+        let json = r#"{
+            "id": "seuil", "name": "Seuil", "description": null,
+            "intervals": [ { "duration_secs": 3600, "power_target": 250 } ]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        assert_eq!(program.metrics(250), ProgramMetrics {
+            ftp: 250,
+            duration_secs: 3600,
+            brake_level_secs: 0,
+            work_kj: 900.0,
+            average_power: 250,
+            normalized_power: 250,
+            intensity_factor: 1.0,
+            tss: 100.0,
+        });
+
+        // Les efforts fractionnés pèsent plus que leur moyenne ; le niveau de frein est ignoré
+        let json = r#"{
+            "id": "fractionne", "name": "Fractionné", "description": null,
+            "intervals": [
+                { "repeat": 10, "intervals": [
+                    { "duration_secs": 60, "power_target": 300 },
+                    { "duration_secs": 60, "power_target": 100 }
+                ] },
+                { "duration_secs": 300, "brake_level": 5 }
+            ]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        let metrics = program.metrics(250);
+        assert_eq!((metrics.duration_secs, metrics.brake_level_secs), (1500, 300));
+        assert_eq!((metrics.work_kj, metrics.average_power), (240.0, 200));
+        assert!(metrics.normalized_power > 200 && metrics.normalized_power < 300);
+        // 20 min à IF > 0,8
+        assert!(metrics.intensity_factor > 0.8 && metrics.tss > 21.3);
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let json = r#"{
+            "id": "mon programme", "name": " ", "description": null,
+            "intervals": [
+                { "duration_secs": 60, "power_target": 100 },
+                { "duration_secs": 0, "power_target": 1000 },
+                { "repeat": 2, "intervals": [{ "duration_secs": 30, "power_start": 100, "power_end": 900 }] }
+            ]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        let errors = program.validate(&PowerLimits::default(), None);
+
+        let summary: Vec<_> = errors.iter().map(|e| (e.interval, e.nested.clone(), e.field)).collect();
+        assert_eq!(summary, vec![
+            (None, vec![], "id"),
+            (None, vec![], "name"),
+            (Some(1), vec![], "duration_secs"),
+            (Some(1), vec![], "power_target"),
+            (Some(2), vec![0], "power_end"),
+        ]);
+        assert_eq!(errors[3].reason, ValidationReason::OutOfRange { min: 25, max: Some(400) });
+        assert_eq!(errors[4].message, "Intervalle 3.1, power_end : doit être entre 25 et 400");
+
+        // Niveau de frein au-delà des bornes de l'appareil, acceptable tant qu'elles sont inconnues
+        let json = r#"{
+            "id": "frein", "name": "Frein", "description": null,
+            "intervals": [{ "duration_secs": 60, "brake_level": 20 }]
+        }"#;
+        let program: TrainingProgram = serde_json::from_str(json).unwrap();
+        assert!(program.validate(&PowerLimits::default(), None).is_empty());
+        let errors = program.validate(&PowerLimits::default(), Some((1, 16)));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Intervalle 1, brake_level : doit être entre 1 et 16");
+    }
+
+    #[test]
+    fn test_interval_target_json() {
+        let power: TrainingInterval = serde_json::from_str(
+            r#"{"duration_secs": 60, "power_target": 150, "name": null}"#).unwrap();
+        assert_eq!(power.target, IntervalTarget::Power { power_target: 150 });
+
+        let level: TrainingInterval = serde_json::from_str(
+            r#"{"duration_secs": 60, "brake_level": 8}"#).unwrap();
+        assert_eq!(level.target, IntervalTarget::BrakeLevel { brake_level: 8 });
+
+        let json = serde_json::to_value(&level).unwrap();
+        assert_eq!(json["brake_level"], 8);
+        assert!(json.get("power_target").is_none());
+    }
 }